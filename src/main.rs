@@ -17,22 +17,56 @@ use log::LevelFilter;
 use clap::{Arg, App, AppSettings, SubCommand, ArgMatches};
 
 use mazetool::maze::{MAZE_DIMENSION_MIN, MAZE_DIMENSION_MAX, MAZE_DIMENSION_DEFAULT};
-use mazetool::maze::Dimensions;
+use mazetool::maze::{ Dimensions, Maze };
 use mazetool::mazecontrol::MazeControl;
 use mazetool::userinterface::UserInterface;
 use mazetool::cli::CommandLineInterface;
 use mazetool::gui::GraphicalInterface;
 use mazetool::common::Job;
 use mazetool::common::SolveMethod;
+use mazetool::common::GenMethod;
+use mazetool::common::{ AppError, DEFAULT_MAX_RETRIES, retry };
+use mazetool::theme::Theme;
 
 struct Config
 {
 	use_gui: bool,
 	show_distances: bool,
 	solve: Option<SolveMethod>,
+	algorithm: GenMethod,
 	dimensions: Dimensions,
+	max_retries: usize,
+	seed: Option<u64>,
+	png: Option<String>,
+	dot: Option<String>,
+	load: Option<String>,
+	validate: bool,
+	output: Option<String>,
+	windowed: Option<(f32, f32)>,
+	braid: Option<f32>,
+	ascii: bool,
+	lines: bool,
+	raw: bool,
+	headless: bool,
+	stats: bool,
+	hard: bool,
+	log_level: LevelFilter,
+	threads: Option<usize>,
+	count: usize,
+	theme: Theme,
+	bench_rounds: Option<u32>,
 }
 
+/// Exit code for a successful run
+const EXIT_SUCCESS: i32 = 0;
+/// Exit code for an error while generating, loading, solving or saving a maze
+const EXIT_FAILURE: i32 = 1;
+/// Exit code for invalid command line arguments
+const EXIT_INVALID_ARGUMENTS: i32 = 2;
+
+/// Default window size for the windowed graphical interface
+const DEFAULT_WINDOW_SIZE: (f32, f32) = (1024.0, 768.0);
+
 impl Config
 {
 	fn new() -> Self
@@ -41,10 +75,31 @@ impl Config
 			use_gui: false,
 			show_distances: false,
 			solve: None,
+			algorithm: GenMethod::Backtracker,
 			dimensions: Dimensions {
 				width: MAZE_DIMENSION_DEFAULT,
 				height: MAZE_DIMENSION_DEFAULT 
-			}
+			},
+			max_retries: DEFAULT_MAX_RETRIES,
+			seed: None,
+			png: None,
+			dot: None,
+			load: None,
+			validate: false,
+			output: None,
+			windowed: None,
+			braid: None,
+			ascii: false,
+			lines: false,
+			raw: false,
+			headless: false,
+			stats: false,
+			hard: false,
+			log_level: LevelFilter::Info,
+			threads: None,
+			count: 1,
+			theme: Theme::default(),
+			bench_rounds: None,
 		}
 	}
 }
@@ -52,12 +107,6 @@ impl Config
 /// Main, the entry poin for the application.
 fn main()
 {
-	//SimpleLogger::new().with_level(LevelFilter::Off).init().unwrap_or_else(|_| ::std::process::exit(1));
-	SimpleLogger::new()
-        .with_level(LevelFilter::Info)
-        .with_utc_timestamps()
-        .init().unwrap_or_else(|_| ::std::process::exit(1));
-
 	// from_ui_tx - send from ui to control
 	// from_ui_rx - receive from ui to control
 	// to_ui_tx   - send to ui from control
@@ -66,10 +115,24 @@ fn main()
 	let (to_ui_tx, to_ui_rx) = unbounded();
 	let mut config = Config::new();
 
-	info!("Parsing command line parameters");
 	if !parse_args(&mut config)
 	{
-		return;
+		::std::process::exit(EXIT_INVALID_ARGUMENTS);
+	}
+
+	SimpleLogger::new()
+        .with_level(config.log_level)
+        .with_utc_timestamps()
+        .init().unwrap_or_else(|_| ::std::process::exit(1));
+
+	if config.headless
+	{
+		::std::process::exit(run_headless(&config));
+	}
+
+	if let Some(rounds) = config.bench_rounds
+	{
+		::std::process::exit(run_benchmarks(rounds));
 	}
 
 	info!("Creating control");
@@ -78,41 +141,204 @@ fn main()
 
 	info!("Creating user interface");
 
-	from_ui_tx.send(Job::GenerateMaze(config.dimensions)).unwrap();
-
-	//TODO: works here (but not after constructing gui) (which is what i need)
-	if let Some(solve_method) = config.solve
+	from_ui_tx.send(Job::SetMaxRetries(config.max_retries)).unwrap();
+	if let Some(seed) = config.seed
+	{
+		from_ui_tx.send(Job::SetSeed(seed)).unwrap();
+	}
+	if let Some(ratio) = config.braid
+	{
+		from_ui_tx.send(Job::SetBraid(ratio)).unwrap();
+	}
+	if config.hard
 	{
-		from_ui_tx.send(Job::SolveMaze(solve_method)).unwrap();
+		from_ui_tx.send(Job::SetHard(true)).unwrap();
+	}
+	if let Some(threads) = config.threads
+	{
+		from_ui_tx.send(Job::SetThreads(threads)).unwrap();
+	}
+	if let Some(filename) = config.load.clone()
+	{
+		from_ui_tx.send(Job::LoadMaze(filename)).unwrap();
+	}
+	else if let Some(solve_method) = config.solve
+	{
+		// solved in the same job, so a failed generation is never solved
+		from_ui_tx.send(Job::GenerateAndSolve(config.dimensions, config.algorithm, solve_method)).unwrap();
 	}
 	else
 	{
-		//from_ui_tx.send(Job::SolveMaze(SolveMethod::GraphElimination)).unwrap();
+		from_ui_tx.send(Job::GenerateMaze(config.dimensions, config.algorithm)).unwrap();
+	}
+
+	if config.stats
+	{
+		from_ui_tx.send(Job::ShowStats).unwrap();
+	}
+
+	if config.validate
+	{
+		from_ui_tx.send(Job::ValidateMaze).unwrap();
 	}
 
-	//std::thread::sleep(std::time::Duration::from_millis(1000));
+	if let Some(filename) = config.output.clone()
+	{
+		from_ui_tx.send(Job::SaveMaze(filename)).unwrap();
+	}
+
+	if let Some(filename) = config.png.clone()
+	{
+		from_ui_tx.send(Job::ExportPng(filename)).unwrap();
+	}
+
+	if let Some(filename) = config.dot.clone()
+	{
+		from_ui_tx.send(Job::ExportDot(filename)).unwrap();
+	}
 
 	if config.use_gui
 	{
+		from_ui_tx.send(Job::StartInteractive).unwrap();
 		let mut ui = Box::new(GraphicalInterface::new(from_ui_tx.clone(), to_ui_rx));
+		if let Some((width, height)) = config.windowed
+		{
+			ui.set_windowed(width, height);
+		}
+		ui.set_gen_method(config.algorithm);
+		ui.set_theme(config.theme);
 		ui.run(config.show_distances);
 	}
 	else
 	{
+		// nothing more to do after the jobs given on the command line
+		from_ui_tx.send(Job::Quit).unwrap();
 		let mut ui = Box::new(CommandLineInterface::new(from_ui_tx.clone(), to_ui_rx));
-		ui.run(false);
+		ui.set_ascii(config.ascii);
+		ui.set_lines(config.lines);
+		ui.set_raw(config.raw);
+		ui.run(config.show_distances);
 	};
 
-	//if let Some(solve_method) = config.solve
-	//{
-	//	from_ui_tx.send(Job::SolveMaze(solve_method)).unwrap();
-	//}
-
 	info!("Main (UI) thread waiting for children to join");
-	control_handle.join().unwrap_or_else(|_| return);
+	let exit_code = match control_handle.join()
+	{
+		Ok(None) => EXIT_SUCCESS,
+		Ok(Some(e)) => {
+			error!("Control finished with an error: {}", e);
+			EXIT_FAILURE
+		},
+		Err(_) => EXIT_FAILURE,
+	};
 
 	info!("Main thread exiting");
 	io::stdout().flush().unwrap();
+	::std::process::exit(exit_code);
+}
+
+/// Generate and save mazes without the control thread or a user interface
+///
+/// The mazes are printed to the standard output if no output file is given,
+/// statistics and errors go to the standard error then.
+/// When generating several mazes, `{n}` in the output file names is
+/// replaced with the number of the maze, starting from 1.
+///
+/// # Parameters
+///
+/// * `config`          - Configuration parsed from the command line
+///
+/// Returns the process exit code, EXIT_SUCCESS on success.
+///
+fn run_headless(config: &Config) -> i32
+{
+	let mut maze = Maze::new();
+	if let Some(seed) = config.seed
+	{
+		maze.set_seed(seed);
+	}
+
+	for n in 1..=config.count
+	{
+		let filename = config.output.as_ref().map(|template| numbered_filename(template, n));
+		let png = config.png.as_ref().map(|template| numbered_filename(template, n));
+		let dot = config.dot.as_ref().map(|template| numbered_filename(template, n));
+		if let Err(e) = generate_and_save(config, &mut maze, filename.as_deref(), png.as_deref(), dot.as_deref())
+		{
+			eprintln!("{}", e);
+			return EXIT_FAILURE;
+		}
+	}
+
+	EXIT_SUCCESS
+}
+
+/// Replace `{n}` in a file name template with the number of a maze
+fn numbered_filename(template: &str, n: usize) -> String
+{
+	template.replace("{n}", &n.to_string())
+}
+
+/// Measure generating and solving mazes and print the results
+///
+/// # Parameters
+///
+/// * `rounds`          - Number of times each operation is measured
+///
+/// Returns the process exit code, EXIT_SUCCESS on success.
+///
+fn run_benchmarks(rounds: u32) -> i32
+{
+	let sizes = &mazetool::benchmark::BENCHMARK_SIZES;
+	match mazetool::benchmark::run_benchmarks(&mut io::stdout(), sizes, rounds)
+	{
+		Ok(_) => EXIT_SUCCESS,
+		Err(e) => {
+			eprintln!("{}", e);
+			EXIT_FAILURE
+		},
+	}
+}
+
+fn generate_and_save(config: &Config, maze: &mut Maze, filename: Option<&str>,
+                     png: Option<&str>, dot: Option<&str>) -> Result<(), AppError>
+{
+	retry(config.max_retries, "generate a maze", |_attempt| {
+		maze.generate(config.dimensions, config.algorithm)
+	})?;
+	if let Some(ratio) = config.braid
+	{
+		maze.braid_randomly(ratio);
+	}
+	if config.hard
+	{
+		maze.place_endpoints_at_extremes()?;
+	}
+
+	// keep the standard output loadable when the maze is printed to it
+	if config.stats && filename.is_none()
+	{
+		eprintln!("{}", maze.stats_summary());
+	}
+	else if config.stats
+	{
+		println!("{}", maze.stats_summary());
+	}
+
+	match filename
+	{
+		Some(filename) => maze.save(filename)?,
+		None => maze.write_maze(&mut io::stdout())?,
+	}
+	if let Some(png) = png
+	{
+		maze.write_to_png(png, mazetool::export::DEFAULT_CELL_PX)?;
+	}
+	if let Some(dot) = dot
+	{
+		maze.write_to_dot(dot)?;
+	}
+
+	Ok(())
 }
 
 /// Parse command line arguments
@@ -126,7 +352,29 @@ fn parse_args(config: &mut Config) -> bool
 	                      .setting(AppSettings::SubcommandRequiredElseHelp)
 	                      .args_from_usage("
 	                           --gui                'Use graphical interface'
-	                           --distances          'Show calculated manhattan distances'")
+	                           --distances          'Show calculated distances from the start'
+	                           --ascii              'Show the maze using only ASCII characters'
+	                           --raw                'Print the maze in the maze file format'
+	                           --headless           'Only generate and save the maze, then exit'
+	                           --stats              'Show the numbers of passages and dead ends'
+	                           --hard               'Place the start and the end as far from each other as possible'
+	                           --max-retries=[n]    'Maximum number of attempts for generating a maze'
+	                           --seed=[n]           'Seed for generating a reproducible maze'
+	                           --threads=[n]        'Compute distances from the start with n threads'
+	                           --count=[n]          'Generate and save n mazes, replacing {n} in the file names with the number'
+	                           --theme=[name]       'Colors of the graphical interface: classic, dark or highcontrast'
+	                           --braid=[ratio]      'Remove this share (0.0-1.0) of dead ends to create loops'
+	                           --png=[file]         'Save the maze as a PNG image'
+	                           --dot=[file]         'Save the topology graph of the maze in the Graphviz DOT format'
+	                           --output=[file]      'Save the maze to a .maze, .maze.gz, .json, .png or .svg file'")
+	                      .arg(Arg::from_usage("-q, --quiet    'Only log errors'")
+		                      .conflicts_with("verbose"))
+	                      .arg(Arg::from_usage("-v, --verbose    'Log debug messages too'"))
+	                      .arg(Arg::from_usage("--ascii-art    'Draw the walls as thin lines with box-drawing characters'")
+		                      .conflicts_with("ascii"))
+	                      .arg(Arg::from_usage("--windowed=[WxH]    'Use a window instead of fullscreen, 1024x768 by default'")
+		                      .min_values(0)
+		                      .require_equals(true))
 	                      .subcommand(SubCommand::with_name("generate")
 	                                      .about("generates a new maze")
 	                                      .arg(Arg::with_name("x")
@@ -135,38 +383,250 @@ fn parse_args(config: &mut Config) -> bool
 	                                      .arg(Arg::with_name("y")
 		                                      .required(true)
 		                                      .help("Height of the maze"))
+	                                      .arg(Arg::with_name("algorithm")
+		                                      .long("algorithm")
+		                                      .takes_value(true)
+		                                      .help("backtracker, prim, kruskal or aldous-broder"))
+	                                      .arg(Arg::with_name("then-solve")
+		                                      .long("then-solve")
+		                                      .takes_value(true)
+		                                      .help("Solve the generated maze with the given method"))
 	                      )
 	                      .subcommand(SubCommand::with_name("solve")
 	                                      .about("solves a given maze")
 	                                      .arg(Arg::with_name("method")
-		                                      .required(true))
-	                                          .help("GraphOnly, GraphElimination or AStar")
+		                                      .required(true)
+		                                      .help("GraphOnly, GraphElimination, AStar, Bfs, Dijkstra or WallFollower"))
 	                                      .arg(Arg::with_name("x")
 		                                      .required(false)
 		                                      .help("Width of the maze"))
 	                                      .arg(Arg::with_name("y")
 		                                      .required(false)
 		                                      .help("Height of the maze"))
+	                                      .arg(Arg::with_name("algorithm")
+		                                      .long("algorithm")
+		                                      .takes_value(true)
+		                                      .help("backtracker, prim, kruskal or aldous-broder"))
+	                      )
+	                      .subcommand(SubCommand::with_name("load")
+	                                      .about("loads a saved maze")
+	                                      .arg(Arg::with_name("file")
+		                                      .required(true)
+		                                      .help("Maze file to load"))
+	                      )
+	                      .subcommand(SubCommand::with_name("validate")
+	                                      .about("checks if a saved maze can be solved")
+	                                      .arg(Arg::with_name("file")
+		                                      .required(true)
+		                                      .help("Maze file to check"))
+	                      )
+	                      .subcommand(SubCommand::with_name("bench")
+	                                      .about("measures generating and solving mazes")
+	                                      .setting(AppSettings::Hidden)
+	                                      .arg(Arg::with_name("rounds")
+		                                      .long("rounds")
+		                                      .takes_value(true)
+		                                      .help("Number of times each operation is measured"))
 	                      )
 	                      .get_matches();
 	
 	if matches.is_present("gui")
 	{
 		config.use_gui = true;
-		if matches.is_present("distances")
-		{
-			config.show_distances = true;
-		}
 	}
 	else
 	{
 		config.use_gui = false;
 	}
-    
+
+	if matches.is_present("quiet")
+	{
+		config.log_level = LevelFilter::Error;
+	}
+	else if matches.is_present("verbose")
+	{
+		config.log_level = LevelFilter::Debug;
+	}
+
+	if matches.is_present("windowed")
+	{
+		match matches.value_of("windowed")
+		{
+			None => {
+				config.windowed = Some(DEFAULT_WINDOW_SIZE);
+			},
+			Some(size) => match parse_window_size(size)
+			{
+				Some(window_size) => {
+					config.windowed = Some(window_size);
+				},
+				None => {
+					println!("Invalid window size specified");
+					return false;
+				}
+			},
+		}
+	}
+
+	if matches.is_present("hard")
+	{
+		config.hard = true;
+	}
+
+	if matches.is_present("stats")
+	{
+		config.stats = true;
+	}
+
+	if matches.is_present("headless")
+	{
+		config.headless = true;
+	}
+
+	if matches.is_present("ascii")
+	{
+		config.ascii = true;
+	}
+
+	if matches.is_present("ascii-art")
+	{
+		config.lines = true;
+	}
+
+	if matches.is_present("raw")
+	{
+		config.raw = true;
+	}
+
+	if matches.is_present("distances")
+	{
+		config.show_distances = true;
+	}
+
+	if let Some(n) = matches.value_of("max-retries")
+	{
+		match n.parse::<usize>()
+		{
+			Ok(max_retries) if max_retries > 0 => {
+				config.max_retries = max_retries;
+			},
+			_ => {
+				println!("Invalid maximum number of retries specified");
+				return false;
+			}
+		}
+	}
+
+	if let Some(r) = matches.value_of("braid")
+	{
+		match r.parse::<f32>()
+		{
+			Ok(ratio) if (0.0..=1.0).contains(&ratio) => {
+				config.braid = Some(ratio);
+			},
+			_ => {
+				println!("Invalid braid ratio specified");
+				return false;
+			}
+		}
+	}
+
+	if let Some(name) = matches.value_of("theme")
+	{
+		match Theme::from_str(name)
+		{
+			Ok(theme) => {
+				config.theme = theme;
+			},
+			Err(_) => {
+				println!("Invalid theme specified");
+				return false;
+			}
+		}
+	}
+
+	if let Some(filename) = matches.value_of("output")
+	{
+		config.output = Some(filename.to_string());
+	}
+
+	if let Some(filename) = matches.value_of("png")
+	{
+		config.png = Some(filename.to_string());
+	}
+
+	if let Some(filename) = matches.value_of("dot")
+	{
+		config.dot = Some(filename.to_string());
+	}
+
+	if let Some(n) = matches.value_of("seed")
+	{
+		match n.parse::<u64>()
+		{
+			Ok(seed) => {
+				config.seed = Some(seed);
+			},
+			Err(_e) => {
+				println!("Invalid seed specified");
+				return false;
+			}
+		}
+	}
+
+	if let Some(n) = matches.value_of("threads")
+	{
+		match n.parse::<usize>()
+		{
+			Ok(threads) if threads > 0 => {
+				config.threads = Some(threads);
+			},
+			_ => {
+				println!("Invalid number of threads specified");
+				return false;
+			}
+		}
+	}
+
+	if let Some(n) = matches.value_of("count")
+	{
+		match n.parse::<usize>()
+		{
+			Ok(count) if count > 0 => {
+				config.count = count;
+			},
+			_ => {
+				println!("Invalid number of mazes specified");
+				return false;
+			}
+		}
+		if config.count > 1
+		{
+			if let Err(message) = check_numbered_filenames(config)
+			{
+				println!("{}", message);
+				return false;
+			}
+			config.headless = true;
+		}
+	}
+
 	if let Some(generate_matches) = matches.subcommand_matches("generate")
 	{
 		info!("Generate requested");
-		success = parse_dimensions(config, generate_matches);
+		success = parse_algorithm(config, generate_matches) && parse_dimensions(config, generate_matches);
+		if let Some(m) = generate_matches.value_of("then-solve")
+		{
+			if let Ok(method) = SolveMethod::from_str(m)
+			{
+				config.solve = Some(method);
+			}
+			else
+			{
+				println!("Invalid solve method specified");
+				success = false;
+			}
+		}
 	}
 
 	if let Some(solve_matches) = matches.subcommand_matches("solve")
@@ -184,55 +644,210 @@ fn parse_args(config: &mut Config) -> bool
 				success = false;
 			}
 		}
-		if success == true
+		if success
 		{
-			success = parse_dimensions(config, solve_matches);
+			success = parse_algorithm(config, solve_matches) && parse_dimensions(config, solve_matches);
+		}
+	}
+
+	if let Some(load_matches) = matches.subcommand_matches("load")
+	{
+		info!("Load requested");
+		if let Some(filename) = load_matches.value_of("file")
+		{
+			config.load = Some(filename.to_string());
+			success = true;
+		}
+	}
+
+	if let Some(validate_matches) = matches.subcommand_matches("validate")
+	{
+		info!("Validate requested");
+		if let Some(filename) = validate_matches.value_of("file")
+		{
+			config.load = Some(filename.to_string());
+			config.validate = true;
+			success = true;
+		}
+	}
+
+	if let Some(bench_matches) = matches.subcommand_matches("bench")
+	{
+		info!("Benchmark requested");
+		let rounds = match bench_matches.value_of("rounds")
+		{
+			Some(n) => n.parse::<u32>().ok(),
+			None => Some(mazetool::benchmark::BENCHMARK_ROUNDS),
+		};
+		match rounds
+		{
+			Some(rounds) if rounds > 0 => {
+				config.bench_rounds = Some(rounds);
+				success = true;
+			},
+			_ => {
+				println!("Invalid number of rounds specified");
+				success = false;
+			}
+		}
+	}
+
+	if success
+	{
+		if let Err(message) = check_headless(config)
+		{
+			println!("{}", message);
+			success = false;
 		}
 	}
 
     return success;
 }
 
+/// Check that the requested job can be done without a user interface
+///
+/// Headless mode only generates and saves new mazes.
+///
+/// # Parameters
+///
+/// * `config`          - Configuration parsed from the command line
+///
+/// Returns a message explaining why the job can't be done headless on failure.
+///
+fn check_headless(config: &Config) -> Result<(), String>
+{
+	if !config.headless
+	{
+		return Ok(());
+	}
+	if config.load.is_some()
+	{
+		return Err("Loading and validating a maze is not supported with --headless or --count".to_string());
+	}
+	if config.solve.is_some()
+	{
+		return Err("Solving a maze is not supported with --headless or --count".to_string());
+	}
+
+	Ok(())
+}
+
+/// Check that every file saved for several mazes gets a name of its own
+///
+/// # Parameters
+///
+/// * `config`          - Configuration parsed from the command line
+///
+/// Returns a message naming the file that would be overwritten on failure.
+///
+fn check_numbered_filenames(config: &Config) -> Result<(), String>
+{
+	if !config.output.as_ref().is_some_and(|filename| filename.contains("{n}"))
+	{
+		return Err("Generating several mazes requires an --output file name containing {n}".to_string());
+	}
+	for (option, filename) in [("--png", &config.png), ("--dot", &config.dot)]
+	{
+		if filename.as_ref().is_some_and(|filename| !filename.contains("{n}"))
+		{
+			return Err(format!("Generating several mazes requires a {} file name containing {{n}}", option));
+		}
+	}
+
+	Ok(())
+}
+
+fn parse_algorithm(config: &mut Config, matches: &ArgMatches<'_>) -> bool
+{
+	if let Some(a) = matches.value_of("algorithm")
+	{
+		if let Ok(algorithm) = GenMethod::from_str(a)
+		{
+			config.algorithm = algorithm;
+		}
+		else
+		{
+			println!("Invalid generation algorithm specified");
+			return false;
+		}
+	}
+
+	true
+}
+
+/// Parse a window size given as WIDTHxHEIGHT
+fn parse_window_size(size: &str) -> Option<(f32, f32)>
+{
+	let mut parts = size.split('x');
+	let width = parts.next()?.trim().parse::<u32>().ok()?;
+	let height = parts.next()?.trim().parse::<u32>().ok()?;
+	if parts.next().is_some() || width == 0 || height == 0
+	{
+		return None;
+	}
+
+	Some((width as f32, height as f32))
+}
+
+/// Parse the width and the height of the maze
+///
+/// Every invalid value is reported to the standard error.
 fn parse_dimensions(config: &mut Config, matches: &ArgMatches<'_>) -> bool
 {
+	let mut valid = true;
+
 	if let Some(x) = matches.value_of("x")
 	{
-		if let Ok(w) = x.parse()
+		match parse_dimension("width", x)
 		{
-			if w >= MAZE_DIMENSION_MIN && w <= MAZE_DIMENSION_MAX
-			{
-				config.dimensions.width = w;
-			}
-			else
-			{
-				return false;
-			}
+			Ok(w) => config.dimensions.width = w,
+			Err(message) => {
+				eprintln!("{}", message);
+				valid = false;
+			},
 		}
 	}
 	if let Some(y) = matches.value_of("y")
 	{
-		// same as above, written in a different way
-		match y.parse()
+		match parse_dimension("height", y)
 		{
-			Ok(h) => {
-				if h >= MAZE_DIMENSION_MIN && h <= MAZE_DIMENSION_MAX
-				{
-					config.dimensions.height = h;
-				}
+			Ok(h) => config.dimensions.height = h,
+			Err(message) => {
+				eprintln!("{}", message);
+				valid = false;
 			},
-			Err(_e) => {
-				return false;
-			}
 		}
 	}
 
-	true
+	config.dimensions = config.dimensions.normalize();
+
+	valid
+}
+
+/// Parse a width or a height of the maze
+///
+/// # Parameters
+///
+/// * `name`            - Name of the dimension used in the error message
+/// * `value`           - Value given on the command line
+///
+/// Returns a message explaining why the value is invalid on failure.
+///
+fn parse_dimension(name: &str, value: &str) -> Result<usize, String>
+{
+	match value.parse::<usize>()
+	{
+		Ok(n) if (MAZE_DIMENSION_MIN..=MAZE_DIMENSION_MAX).contains(&n) => Ok(n),
+		Ok(n) => Err(format!("{} {} out of range [{}, {}]", name, n, MAZE_DIMENSION_MIN, MAZE_DIMENSION_MAX)),
+		Err(_) => Err(format!("{} '{}' is not a whole number", name, value)),
+	}
 }
 
 #[cfg(test)]
 mod tests
 {
 	use super::*;
+	use mazetool::common::UIRequest;
 
 	#[test]
 	fn create_cli()
@@ -258,5 +873,505 @@ mod tests
 		let _ = MazeControl::run(from_ui_rx, to_ui_tx);
 		from_ui_tx.send(Job::Quit).unwrap();
 	}
-}
 
+	#[test]
+	fn solve_generated_maze_with_graph()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 19, height: 19 }, GenMethod::Backtracker)).unwrap();
+		from_ui_tx.send(Job::SolveMaze(SolveMethod::GraphOnly)).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		let maze = to_ui_rx.try_iter().filter_map(|request| match request
+		{
+			UIRequest::ShowMaze(maze) => Some(maze),
+			_ => None,
+		}).last().unwrap();
+		let m = maze.lock().unwrap();
+		assert!(m.cells.iter().any(|c| c.on_route));
+		assert!(m.cells[m.start].on_route);
+		assert!(m.cells[m.end].on_route);
+	}
+
+	#[test]
+	fn graph_is_created_for_each_generated_maze()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		for _ in 0..2
+		{
+			from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 19, height: 19 }, GenMethod::Backtracker)).unwrap();
+			from_ui_tx.send(Job::SolveMaze(SolveMethod::GraphOnly)).unwrap();
+		}
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		let maze = to_ui_rx.try_iter().filter_map(|request| match request
+		{
+			UIRequest::ShowMaze(maze) => Some(maze),
+			_ => None,
+		}).last().unwrap();
+		let m = maze.lock().unwrap();
+		assert!(m.graph_created);
+		assert!(m.cells.iter().any(|c| c.nodes.iter().any(|n| n.is_some())));
+		assert!(m.cells[m.end].on_route);
+	}
+
+	#[test]
+	fn load_missing_maze_shows_error()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::LoadMaze("/nonexistent/missing.maze".to_string())).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		assert!(handle.join().unwrap().is_some());
+
+		assert!(to_ui_rx.try_iter().any(|request| matches!(request, UIRequest::ShowError(_))));
+	}
+
+	#[test]
+	fn save_generated_maze()
+	{
+		let mut path = std::env::temp_dir();
+		path.push(format!("mazetool_{}_output.maze", std::process::id()));
+		let filename = path.to_str().unwrap().to_string();
+
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, _to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 21, height: 15 }, GenMethod::Backtracker)).unwrap();
+		from_ui_tx.send(Job::SaveMaze(filename.clone())).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		assert!(handle.join().unwrap().is_none());
+
+		let mut maze = Maze::new();
+		maze.read_from_file(&filename).unwrap();
+		std::fs::remove_file(&filename).unwrap();
+		assert_eq!(maze.dimensions.width, 21);
+		assert_eq!(maze.dimensions.height, 15);
+	}
+
+	#[test]
+	fn export_generated_maze_graph()
+	{
+		let mut path = std::env::temp_dir();
+		path.push(format!("mazetool_{}_graph.dot", std::process::id()));
+		let filename = path.to_str().unwrap().to_string();
+
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 21, height: 15 }, GenMethod::Kruskal)).unwrap();
+		from_ui_tx.send(Job::ExportDot(filename.clone())).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		assert!(handle.join().unwrap().is_none());
+
+		let dot = std::fs::read_to_string(&filename).unwrap();
+		std::fs::remove_file(&filename).unwrap();
+		assert!(dot.starts_with("digraph"));
+		assert!(dot.contains(" -> "));
+		assert!(!to_ui_rx.try_iter().any(|request| matches!(request, UIRequest::ShowError(_))));
+	}
+
+	#[test]
+	fn headless_saves_generated_maze()
+	{
+		let mut path = std::env::temp_dir();
+		path.push(format!("mazetool_{}_headless.maze", std::process::id()));
+		let filename = path.to_str().unwrap().to_string();
+
+		let mut config = Config::new();
+		config.output = Some(filename.clone());
+		config.dimensions = Dimensions { width: 51, height: 51 };
+		assert_eq!(run_headless(&config), EXIT_SUCCESS);
+
+		let mut maze = Maze::new();
+		maze.read_from_file(&filename).unwrap();
+		std::fs::remove_file(&filename).unwrap();
+		assert_eq!(maze.dimensions.width, 51);
+		assert!(maze.is_solvable());
+	}
+
+	#[test]
+	fn headless_saves_several_mazes()
+	{
+		let mut path = std::env::temp_dir();
+		path.push(format!("mazetool_{}_count_{{n}}.maze", std::process::id()));
+		let template = path.to_str().unwrap().to_string();
+
+		let mut config = Config::new();
+		config.output = Some(template.clone());
+		config.count = 3;
+		config.seed = Some(5);
+		assert_eq!(run_headless(&config), EXIT_SUCCESS);
+
+		let mut mazes = Vec::new();
+		for n in 1..=3
+		{
+			let filename = numbered_filename(&template, n);
+			let mut maze = Maze::new();
+			maze.read_from_file(&filename).unwrap();
+			std::fs::remove_file(&filename).unwrap();
+			mazes.push(maze);
+		}
+		assert!(mazes[0] != mazes[1]);
+		assert!(mazes[1] != mazes[2]);
+		assert!(mazes[0] != mazes[2]);
+	}
+
+	#[test]
+	fn several_mazes_need_numbered_filenames()
+	{
+		let mut config = Config::new();
+		config.count = 3;
+		assert!(check_numbered_filenames(&config).is_err());
+
+		config.output = Some("maze_{n}.maze".to_string());
+		assert_eq!(check_numbered_filenames(&config), Ok(()));
+
+		config.png = Some("maze.png".to_string());
+		assert!(check_numbered_filenames(&config).unwrap_err().contains("--png"));
+		config.png = Some("maze_{n}.png".to_string());
+		config.dot = Some("maze.dot".to_string());
+		assert!(check_numbered_filenames(&config).unwrap_err().contains("--dot"));
+		config.dot = Some("maze_{n}.dot".to_string());
+		assert_eq!(check_numbered_filenames(&config), Ok(()));
+	}
+
+	#[test]
+	fn headless_only_generates()
+	{
+		let mut config = Config::new();
+		config.headless = true;
+		assert_eq!(check_headless(&config), Ok(()));
+
+		config.load = Some("saved.maze".to_string());
+		assert!(check_headless(&config).is_err());
+
+		config.load = None;
+		config.solve = Some(SolveMethod::AStar);
+		assert!(check_headless(&config).is_err());
+
+		config.headless = false;
+		assert_eq!(check_headless(&config), Ok(()));
+	}
+
+	#[test]
+	fn solve_maze_step_by_step()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 19, height: 19 }, GenMethod::Backtracker)).unwrap();
+		for _ in 0..3
+		{
+			from_ui_tx.send(Job::SolveStep).unwrap();
+		}
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		let requests: Vec<UIRequest> = to_ui_rx.try_iter().collect();
+		let steps: Vec<_> = requests.iter().filter_map(|request| match request
+		{
+			UIRequest::ShowMazeWithMeta { maze, solved, method } => Some((maze, *solved, *method)),
+			_ => None,
+		}).collect();
+		assert_eq!(steps.len(), 3);
+		assert!(steps.iter().all(|(_, solved, method)| !solved && *method == Some(SolveMethod::AStar)));
+		let m = steps[2].0.lock().unwrap();
+		assert!(m.cells.iter().any(|c| c.visited));
+		assert!(!m.cells[m.end].on_route);
+	}
+
+	#[test]
+	fn generate_maze_step_by_step()
+	{
+		let dimensions = Dimensions { width: 15, height: 11 };
+		let mut generated = Maze::new();
+		generated.set_seed(9);
+		generated.generate(dimensions, GenMethod::Prim).unwrap();
+
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::SetSeed(9)).unwrap();
+
+		// one step for each room after the first, and one to finish
+		let rooms = 7 * 5;
+		for _ in 0..rooms
+		{
+			from_ui_tx.send(Job::GenerateStep(dimensions, GenMethod::Prim)).unwrap();
+		}
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		let mazes: Vec<_> = to_ui_rx.try_iter().filter_map(|request| match request
+		{
+			UIRequest::ShowMaze(maze) => Some(maze),
+			_ => None,
+		}).collect();
+		assert_eq!(mazes.len(), rooms);
+		let m = mazes.last().unwrap().lock().unwrap();
+		assert!(!m.is_generating());
+		assert_eq!(*m, generated);
+	}
+
+	#[test]
+	fn parse_valid_window_size()
+	{
+		assert_eq!(parse_window_size("800x600"), Some((800.0, 600.0)));
+	}
+
+	#[test]
+	fn parse_invalid_window_size()
+	{
+		assert_eq!(parse_window_size("800"), None);
+		assert_eq!(parse_window_size("800x"), None);
+		assert_eq!(parse_window_size("0x600"), None);
+		assert_eq!(parse_window_size("800x600x2"), None);
+	}
+
+	#[test]
+	fn parse_dimension_values()
+	{
+		assert_eq!(parse_dimension("width", "21"), Ok(21));
+		assert_eq!(parse_dimension("height", &MAZE_DIMENSION_MAX.to_string()), Ok(MAZE_DIMENSION_MAX));
+		assert_eq!(parse_dimension("width", "5"), Err("width 5 out of range [10, 10000]".to_string()));
+		assert_eq!(parse_dimension("height", "10001"), Err("height 10001 out of range [10, 10000]".to_string()));
+		assert_eq!(parse_dimension("height", "abc"), Err("height 'abc' is not a whole number".to_string()));
+		assert_eq!(parse_dimension("width", "-3"), Err("width '-3' is not a whole number".to_string()));
+	}
+
+	#[test]
+	fn interactive_errors_keep_exit_code()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 11, height: 11 }, GenMethod::Backtracker)).unwrap();
+		from_ui_tx.send(Job::StartInteractive).unwrap();
+		from_ui_tx.send(Job::SetEndpoints(0, 0)).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		assert!(handle.join().unwrap().is_none());
+
+		assert!(to_ui_rx.try_iter().any(|request| matches!(request, UIRequest::ShowError(_))));
+	}
+
+	#[test]
+	fn error_after_user_interface_quit_sets_exit_code()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		drop(to_ui_rx);
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::LoadMaze("/nonexistent/missing.maze".to_string())).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		assert!(handle.join().unwrap().is_some());
+	}
+
+	#[test]
+	fn endpoints_on_walls_show_error()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 11, height: 11 }, GenMethod::Backtracker)).unwrap();
+		from_ui_tx.send(Job::SetEndpoints(0, 0)).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		assert!(to_ui_rx.try_iter().any(|request| matches!(request, UIRequest::ShowError(_))));
+	}
+
+	#[test]
+	fn loaded_maze_is_shown_first()
+	{
+		let filename = std::env::temp_dir().join("mazetool_loaded_maze_is_shown_first.maze");
+		let filename = filename.to_str().unwrap().to_string();
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 15 }, GenMethod::Backtracker).unwrap();
+		maze.write_to_file(&filename).unwrap();
+
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::LoadMaze(filename.clone())).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+		std::fs::remove_file(&filename).unwrap();
+
+		let shown = to_ui_rx.try_iter().find_map(|request| match request
+		{
+			UIRequest::ShowMaze(maze) => Some(maze),
+			_ => None,
+		}).unwrap();
+		let shown = shown.lock().unwrap();
+		assert_eq!(shown.dimensions, Dimensions { width: 21, height: 15 });
+		assert_eq!(*shown, maze);
+	}
+
+	#[test]
+	fn solve_again_with_another_method()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker)).unwrap();
+		from_ui_tx.send(Job::SolveMaze(SolveMethod::Bfs)).unwrap();
+		from_ui_tx.send(Job::ClearSolution).unwrap();
+		from_ui_tx.send(Job::SolveMaze(SolveMethod::WallFollower)).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		let maze = to_ui_rx.try_iter().filter_map(|request| match request
+		{
+			UIRequest::ShowMaze(maze) => Some(maze),
+			_ => None,
+		}).last().unwrap();
+		let mut solved = maze.lock().unwrap().clone();
+		let route = solved.solution_path();
+		assert!(!route.is_empty());
+
+		solved.clear_solution();
+		solved.run_bfs(false);
+		assert_eq!(solved.solution_path(), route);
+	}
+
+	#[test]
+	fn solved_maze_is_shown_with_method()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 15, height: 15 }, GenMethod::Prim)).unwrap();
+		from_ui_tx.send(Job::SolveMaze(SolveMethod::Dijkstra)).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		let requests: Vec<UIRequest> = to_ui_rx.try_iter().collect();
+		assert_eq!(requests.iter().filter(|request| matches!(request, UIRequest::ShowMaze(_))).count(), 1);
+		assert!(requests.iter().any(|request| matches!(request,
+			UIRequest::ShowMazeWithMeta { solved: true, method: Some(SolveMethod::Dijkstra), .. })));
+		assert!(requests.iter().any(|request| matches!(request,
+			UIRequest::ShowInfo(message) if message.starts_with("Dijkstra: ") && message.contains("cells visited"))));
+	}
+
+	#[test]
+	fn generate_then_solve_in_one_job()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateAndSolve(Dimensions { width: 25, height: 17 },
+		                                      GenMethod::Kruskal,
+		                                      SolveMethod::AStar)).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		let maze = to_ui_rx.try_iter().find_map(|request| match request
+		{
+			UIRequest::ShowMazeWithMeta { maze, solved: true, .. } => Some(maze),
+			_ => None,
+		}).unwrap();
+		let m = maze.lock().unwrap();
+		assert_eq!(m.dimensions, Dimensions { width: 25, height: 17 });
+		assert!(m.cells[m.start].on_route);
+		assert!(m.cells[m.end].on_route);
+	}
+
+	#[test]
+	fn failed_generation_is_not_solved()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 15, height: 15 }, GenMethod::Backtracker)).unwrap();
+		from_ui_tx.send(Job::GenerateAndSolve(Dimensions { width: MAZE_DIMENSION_MAX - 1, height: MAZE_DIMENSION_MAX - 1 },
+		                                      GenMethod::Backtracker,
+		                                      SolveMethod::Bfs)).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		let requests: Vec<UIRequest> = to_ui_rx.try_iter().collect();
+		assert!(requests.iter().any(|request| matches!(request, UIRequest::ShowError(_))));
+		assert!(!requests.iter().any(|request| matches!(request, UIRequest::ShowMazeWithMeta { .. })));
+		let maze = requests.iter().find_map(|request| match request
+		{
+			UIRequest::ShowMaze(maze) => Some(maze.clone()),
+			_ => None,
+		}).unwrap();
+		let m = maze.lock().unwrap();
+		assert!(!m.cells.iter().any(|c| c.on_route || c.visited));
+	}
+
+	#[test]
+	fn clear_solution_keeps_the_maze()
+	{
+		let dimensions = Dimensions { width: 21, height: 21 };
+		let mut generated = Maze::new();
+		generated.set_seed(3);
+		generated.generate(dimensions, GenMethod::Prim).unwrap();
+
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::SetSeed(3)).unwrap();
+		from_ui_tx.send(Job::GenerateAndSolve(dimensions, GenMethod::Prim, SolveMethod::Dijkstra)).unwrap();
+		from_ui_tx.send(Job::ClearSolution).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		let requests: Vec<UIRequest> = to_ui_rx.try_iter().collect();
+		assert!(requests.iter().any(|request| matches!(request, UIRequest::ShowMazeWithMeta { solved: true, .. })));
+		let maze = match requests.iter().rev().find(|request| !matches!(request, UIRequest::Quit))
+		{
+			Some(UIRequest::ShowMaze(maze)) => maze.clone(),
+			_ => panic!("Cleared maze was not shown"),
+		};
+		let m = maze.lock().unwrap();
+		assert!(m.cells.iter().all(|c| !c.on_route && !c.visited));
+		assert_eq!(*m, generated);
+	}
+
+	#[test]
+	fn cancel_generation()
+	{
+		let (from_ui_tx, from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let handle = MazeControl::run(from_ui_rx, to_ui_tx);
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 301, height: 301 }, GenMethod::AldousBroder)).unwrap();
+
+		// cancel once the generation has reported its progress
+		let mut requests: Vec<UIRequest> = Vec::new();
+		loop
+		{
+			let request = to_ui_rx.recv_timeout(std::time::Duration::from_secs(10)).unwrap();
+			let started = matches!(request, UIRequest::ShowProgress(_));
+			requests.push(request);
+			if started
+			{
+				break;
+			}
+		}
+		from_ui_tx.send(Job::Cancel).unwrap();
+		from_ui_tx.send(Job::GenerateMaze(Dimensions { width: 11, height: 11 }, GenMethod::Backtracker)).unwrap();
+		from_ui_tx.send(Job::Quit).unwrap();
+		handle.join().unwrap();
+
+		requests.extend(to_ui_rx.try_iter());
+		assert!(requests.iter().any(|request| matches!(request, UIRequest::ShowInfo(info) if info == "Cancelled")));
+		let maze = requests.iter().find_map(|request| match request
+		{
+			UIRequest::ShowMaze(maze) => Some(maze.clone()),
+			_ => None,
+		}).unwrap();
+		let maze = maze.lock().unwrap();
+		assert_eq!(maze.dimensions.width, 11);
+		assert!(maze.is_solvable());
+	}
+}
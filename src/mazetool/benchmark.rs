@@ -0,0 +1,117 @@
+// Mazetool - measuring how long generating and solving mazes takes
+
+use std::io::Write;
+use std::time::{ Duration, Instant };
+
+use super::common::{ AppError, GenMethod, SolveMethod };
+use super::maze::{ Dimensions, Maze };
+
+/// Widths and heights of the square mazes measured by default
+pub const BENCHMARK_SIZES: [usize; 3] = [21, 51, 101];
+
+/// Default number of times each operation is measured
+pub const BENCHMARK_ROUNDS: u32 = 10;
+
+/// Seed used for every generated maze, so runs are comparable
+pub const BENCHMARK_SEED: u64 = 1;
+
+const GEN_METHODS: [(&str, GenMethod); 4] = [
+	("backtracker", GenMethod::Backtracker),
+	("prim", GenMethod::Prim),
+	("kruskal", GenMethod::Kruskal),
+	("aldous-broder", GenMethod::AldousBroder),
+];
+
+const SOLVE_METHODS: [SolveMethod; 6] = [
+	SolveMethod::GraphOnly,
+	SolveMethod::GraphElimination,
+	SolveMethod::AStar,
+	SolveMethod::Bfs,
+	SolveMethod::Dijkstra,
+	SolveMethod::WallFollower,
+];
+
+/// Measure generating mazes with every algorithm and solving them with every method
+///
+/// Every maze is generated from the same seed, so each round works
+/// on an identical maze. Solving is measured on mazes generated
+/// with the backtracker, each round solving a fresh copy of the maze so
+/// the graph solvers also build the graph every time. One line is
+/// written for each measurement, with the mean time of the rounds.
+///
+/// # Parameters
+///
+/// * `writer`          - Target of the results
+/// * `sizes`           - Widths and heights of the square mazes to measure
+/// * `rounds`          - Number of times each operation is measured
+///
+/// Returns AppError if generating a maze or writing the results failed.
+///
+pub fn run_benchmarks<W: Write>(writer: &mut W, sizes: &[usize], rounds: u32) -> Result<(), AppError>
+{
+	let mut maze = Maze::new();
+
+	for &size in sizes
+	{
+		let dimensions = Dimensions { width: size, height: size };
+
+		for (name, method) in GEN_METHODS.iter()
+		{
+			let mut total = Duration::ZERO;
+			for _ in 0..rounds
+			{
+				maze.set_seed(BENCHMARK_SEED);
+				let begin = Instant::now();
+				maze.generate(dimensions, *method)?;
+				total += begin.elapsed();
+			}
+			write_result(writer, "generate", name, dimensions, total, rounds)?;
+		}
+
+		maze.set_seed(BENCHMARK_SEED);
+		maze.generate(dimensions, GenMethod::Backtracker)?;
+		for method in SOLVE_METHODS.iter()
+		{
+			let mut total = Duration::ZERO;
+			for _ in 0..rounds
+			{
+				let mut unsolved = maze.clone();
+				let begin = Instant::now();
+				unsolved.solve(*method, None);
+				total += begin.elapsed();
+			}
+			write_result(writer, "solve", &method.to_string(), dimensions, total, rounds)?;
+		}
+	}
+
+	Ok(())
+}
+
+fn write_result<W: Write>(writer: &mut W, operation: &str, method: &str, dimensions: Dimensions,
+                          total: Duration, rounds: u32) -> Result<(), AppError>
+{
+	let mean = total.as_secs_f64() * 1000.0 / rounds.max(1) as f64;
+	writeln!(writer, "{:<8} {:<16} {:>9} {:>12.3} ms", operation, method, dimensions.to_string(), mean)?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn benchmarks_measure_every_method()
+	{
+		let mut output: Vec<u8> = Vec::new();
+		run_benchmarks(&mut output, &[11, 15], 1).unwrap();
+		let text = String::from_utf8(output).unwrap();
+
+		assert_eq!(text.lines().count(), 2 * (GEN_METHODS.len() + SOLVE_METHODS.len()));
+		assert!(text.lines().all(|line| line.ends_with(" ms")));
+		assert!(text.contains("aldous-broder"));
+		assert!(text.contains("WallFollower"));
+		assert!(text.contains("15x15"));
+	}
+}
@@ -1,23 +1,64 @@
 // Mazetool - command line user interface
 
+use std::io::{ self, Write };
 use std::sync::{ Arc, Mutex };
 
 use crossbeam::channel::{Receiver, Sender};
 
 use super::userinterface::UserInterface;
 use super::common::{ UIRequest, Job };
-use super::maze::Maze;
+use super::maze::{ Maze, MazeCellType };
 
 /// Command line user interface for Mazetool
 pub struct CommandLineInterface
 {
 	#[allow(dead_code)]
 	tx: Sender<Job>,
-	rx: Receiver<UIRequest>
+	rx: Receiver<UIRequest>,
+	show_distances: bool,
+	ascii: bool,
+	lines: bool,
+	raw: bool,
 }
 
 impl CommandLineInterface
 {
+	/// Show mazes using only ASCII characters
+	///
+	/// # Parameters
+	///
+	/// * `ascii`         - True to show walls as '#' instead of block glyphs
+	///
+	pub fn set_ascii(&mut self, ascii: bool)
+	{
+		self.ascii = ascii;
+	}
+
+	/// Show the walls of mazes as thin lines drawn with box-drawing characters
+	///
+	/// # Parameters
+	///
+	/// * `lines`         - True to draw walls as lines instead of block glyphs
+	///
+	pub fn set_lines(&mut self, lines: bool)
+	{
+		self.lines = lines;
+	}
+
+	/// Print mazes in the maze file format instead of decorated text
+	///
+	/// Messages are then printed to the standard error, so the standard
+	/// output can be redirected to a maze file.
+	///
+	/// # Parameters
+	///
+	/// * `raw`           - True to print mazes in the maze file format
+	///
+	pub fn set_raw(&mut self, raw: bool)
+	{
+		self.raw = raw;
+	}
+
 	/// Show an info message in the user interface
 	///
 	/// # Parameters
@@ -26,7 +67,34 @@ impl CommandLineInterface
 	///
 	fn show_info(&self, message: &str)
 	{
-		println!("{}", message);
+		if self.raw
+		{
+			eprintln!("{}", message);
+		}
+		else
+		{
+			println!("{}", message);
+		}
+	}
+
+	/// Show the progress of a long operation on a single line
+	///
+	/// # Parameters
+	///
+	/// * `percent`       - Progress in percent
+	///
+	fn show_progress(&self, percent: f32)
+	{
+		if self.raw
+		{
+			return;
+		}
+		print!("\r{:3.0}%", percent);
+		if percent >= 100.0
+		{
+			println!();
+		}
+		io::stdout().flush().unwrap_or_else(|_| return);
 	}
 
 	/// Show an error message in the user interface
@@ -37,7 +105,14 @@ impl CommandLineInterface
 	///
 	fn show_error(&self, error: &str)
 	{
-		println!("Error: {}", error);
+		if self.raw
+		{
+			eprintln!("Error: {}", error);
+		}
+		else
+		{
+			println!("Error: {}", error);
+		}
 	}
 
 	fn show_maze(&self, maze: Arc<Mutex<Maze>>)
@@ -45,30 +120,18 @@ impl CommandLineInterface
 		match maze.lock()
 		{
 			Ok(m) => {
-				debug!("Size: {} x {}, cells len: {}",
-					   m.dimensions.width,
-					   m.dimensions.height,
-					   m.cells.len());
+				debug!("Size: {}, cells len: {}", m.dimensions, m.cells.len());
 
-				for i in 0..m.dimensions.height
+				if self.raw
 				{
-					for j in 0..m.dimensions.width
+					if let Err(e) = m.write_maze(&mut io::stdout())
 					{
-						let cell = &m.cells[j + (i * m.dimensions.width)];
-						if cell.on_route
-						{
-							print!("o");
-						}
-						else if cell.visited
-						{
-							print!(".");
-						}
-						else
-						{
-							print!("{}", cell.celltype);
-						}
+						self.show_error(&e.to_string());
 					}
-					println!("");
+				}
+				else
+				{
+					print!("{}", self.render_maze(&m));
 				}
 			},
 			Err(e) => {
@@ -77,6 +140,123 @@ impl CommandLineInterface
 		}
 	}
 
+	/// Render the maze as text, one line per row
+	///
+	/// A solved route is shown with arrows pointing towards the end.
+	///
+	/// # Parameters
+	///
+	/// * `m`             - Maze to render
+	///
+	fn render_maze(&self, m: &Maze) -> String
+	{
+		let mut output = String::new();
+
+		// arrows are shown only for a route that can be followed from the start
+		let mut arrows: Vec<Option<char>> = vec![None; m.cells.len()];
+		if let (Some(route), Some(moves)) = (m.route_positions(), m.solution_moves())
+		{
+			for (&position, direction) in route.iter().zip(moves.iter())
+			{
+				if m.cells[position].celltype == MazeCellType::Passage
+				{
+					arrows[position] = Some(direction.to_arrow_char(self.ascii));
+				}
+			}
+		}
+
+		for i in 0..m.dimensions.height
+		{
+			for j in 0..m.dimensions.width
+			{
+				let cell = match m.get(j, i)
+				{
+					Some(cell) => cell,
+					None => continue,
+				};
+				let glyph = if self.ascii
+				{
+					cell.celltype.to_ascii_char().to_string()
+				}
+				else if self.lines && cell.celltype == MazeCellType::Wall
+				{
+					CommandLineInterface::wall_line_char(m, j, i).to_string()
+				}
+				else
+				{
+					cell.celltype.to_string()
+				};
+
+				if let Some(arrow) = arrows[j + i * m.dimensions.width]
+				{
+					output.push(arrow);
+				}
+				else if cell.on_route && cell.celltype == MazeCellType::Passage
+				{
+					output.push('o');
+				}
+				else if cell.on_route
+				{
+					// the start and the end of the route
+					output.push_str(&glyph);
+				}
+				else if cell.visited
+				{
+					output.push('.');
+				}
+				else if self.show_distances && cell.celltype == MazeCellType::Passage && cell.distance.is_some()
+				{
+					// only the last digit fits in one character
+					output.push(cell.text().chars().last().unwrap_or(' '));
+				}
+				else
+				{
+					output.push_str(&glyph);
+				}
+			}
+			output.push('\n');
+		}
+
+		output
+	}
+
+	/// Get the box-drawing character joining a wall cell to its neighboring walls
+	///
+	/// # Parameters
+	///
+	/// * `m`             - Maze the wall is in
+	/// * `x`             - Column of the wall cell
+	/// * `y`             - Row of the wall cell
+	///
+	fn wall_line_char(m: &Maze, x: usize, y: usize) -> char
+	{
+		let is_wall = |x: Option<usize>, y: Option<usize>| match (x, y)
+		{
+			(Some(x), Some(y)) => m.get(x, y).is_some_and(|cell| cell.celltype == MazeCellType::Wall),
+			_ => false,
+		};
+		let north = is_wall(Some(x), y.checked_sub(1));
+		let east = is_wall(x.checked_add(1), Some(y));
+		let south = is_wall(Some(x), y.checked_add(1));
+		let west = is_wall(x.checked_sub(1), Some(y));
+
+		match (north, east, south, west)
+		{
+			(true, true, true, true)        => '┼',
+			(true, true, true, false)       => '├',
+			(true, false, true, true)       => '┤',
+			(false, true, true, true)       => '┬',
+			(true, true, false, true)       => '┴',
+			(false, true, true, false)      => '┌',
+			(false, false, true, true)      => '┐',
+			(true, true, false, false)      => '└',
+			(true, false, false, true)      => '┘',
+			(true, _, _, _) | (_, _, true, _) => '│',
+			(_, true, _, _) | (_, _, _, true) => '─',
+			_                               => '·',
+		}
+	}
+
 	//fn save_maze(&self, maze: Arc<Mutex<Maze>>)
 	//{
 	//	match maze.lock()
@@ -104,7 +284,14 @@ impl CommandLineInterface
 	{
 		let mut keep_running = true;
 		let request = self.rx.recv().unwrap_or_else(|_| UIRequest::Quit);
-		info!("UI received request: {:?}", request);
+		if let UIRequest::ShowProgress(_) = request
+		{
+			debug!("UI received request: {:?}", request);
+		}
+		else
+		{
+			info!("UI received request: {:?}", request);
+		}
 		match request
 		{
 			UIRequest::ShowError(message) => {
@@ -116,6 +303,17 @@ impl CommandLineInterface
 			UIRequest::ShowMaze(maze) => {
 				self.show_maze(maze);
 			},
+			UIRequest::ShowMazeWithMeta { maze, solved, method } => {
+				let length = maze.lock().ok().and_then(|m| m.path_length());
+				self.show_maze(maze);
+				if let (true, Some(method), Some(length)) = (solved, method, length)
+				{
+					self.show_info(&format!("Solved with {}, the route is {} steps long", method, length));
+				}
+			},
+			UIRequest::ShowProgress(percent) => {
+				self.show_progress(percent);
+			},
 			UIRequest::Quit => {
 				keep_running = false;
 			},
@@ -140,11 +338,17 @@ impl UserInterface for CommandLineInterface
 		{
 			tx: tx,
 			rx: rx,
+			show_distances: false,
+			ascii: false,
+			lines: false,
+			raw: false,
 		}
 	}
 
-	fn run(&mut self, _show_distances: bool)
+	fn run(&mut self, show_distances: bool)
 	{
+		self.show_distances = show_distances;
+
 		loop
 		{
 			if self.handle_request() != true
@@ -154,3 +358,111 @@ impl UserInterface for CommandLineInterface
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crossbeam::channel::unbounded;
+	use super::super::maze::Dimensions;
+
+	#[test]
+	fn render_ascii_maze()
+	{
+		let (tx, _) = unbounded();
+		let (_, rx) = unbounded();
+		let mut ui = CommandLineInterface::new(tx, rx);
+		ui.set_ascii(true);
+
+		let mut maze = Maze::new();
+		maze.reset(Dimensions { width: 5, height: 5 }).unwrap();
+		for position in [6, 7, 8, 13, 16, 17, 18]
+		{
+			maze.cells[position].celltype = MazeCellType::Passage;
+		}
+		maze.set_endpoints(6, 16).unwrap();
+		maze.cells[7].on_route = true;
+		maze.cells[13].visited = true;
+
+		assert_eq!(ui.render_maze(&maze), "#####\n#So #\n###.#\n#E  #\n#####\n");
+	}
+
+	#[test]
+	fn route_is_shown_with_arrows()
+	{
+		let (tx, _) = unbounded();
+		let (_, rx) = unbounded();
+		let mut ui = CommandLineInterface::new(tx, rx);
+
+		// an L-shaped route going down from the start, then right and down to the end
+		let mut maze = Maze::from_ascii("
+#S#########
+# #########
+# #########
+# #########
+# #########
+# #########
+# #########
+# #########
+# #########
+#         #
+#########E#
+").unwrap();
+		maze.run_bfs(false);
+
+		let mut expected = vec!["█S█████████"];
+		expected.extend(vec!["█↓█████████"; 8]);
+		expected.push("█→→→→→→→→↓█");
+		expected.push("█████████E█");
+		assert_eq!(ui.render_maze(&maze), expected.join("\n") + "\n");
+
+		ui.set_ascii(true);
+		let mut expected = vec!["#S#########"];
+		expected.extend(vec!["#v#########"; 8]);
+		expected.push("#>>>>>>>>v#");
+		expected.push("#########E#");
+		assert_eq!(ui.render_maze(&maze), expected.join("\n") + "\n");
+	}
+
+	#[test]
+	fn render_walls_as_lines()
+	{
+		let (tx, _) = unbounded();
+		let (_, rx) = unbounded();
+		let mut ui = CommandLineInterface::new(tx, rx);
+		ui.set_lines(true);
+
+		let maze = Maze::from_ascii("
+#S#########
+# #   #   #
+# ### # # #
+#     # # #
+####### # #
+#   #   # #
+# # # ### #
+# #   #   #
+# ### # ###
+#   #     #
+#######E###
+").unwrap();
+
+		let rendered = ui.render_maze(&maze);
+		let rows: Vec<Vec<char>> = rendered.lines().map(|line| line.chars().collect()).collect();
+		assert_eq!(rows.len(), 11);
+		assert!(rows.iter().all(|row| row.len() == 11));
+
+		assert_eq!(&rows[0][..3], &['│', 'S', '┌']);
+		assert_eq!(rows[0][6], '┬');
+		assert_eq!(rows[0][10], '┐');
+		assert_eq!(rows[2][2], '└');
+		assert_eq!(rows[2][6], '│');
+		assert_eq!(rows[4][0], '├');
+		assert_eq!(rows[4][3], '─');
+		assert_eq!(rows[4][6], '┘');
+		assert_eq!(rows[8][10], '┤');
+		assert_eq!(rows[10][0], '└');
+		assert_eq!(rows[10][4], '┴');
+		assert_eq!(rows[10][10], '┘');
+		assert_eq!(rows[1][1], ' ');
+	}
+}
@@ -4,17 +4,40 @@ use std::fmt;
 use std::error::Error;
 use std::io::Error as IOError;
 use std::num::ParseIntError;
-use std::sync::{ Arc, Mutex };
+use std::sync::{ Arc, Mutex, PoisonError };
 use std::str::FromStr;
 
 use super::maze::{ Dimensions, Maze };
 
-#[derive(Debug)]
+/// Default number of attempts for operations that may need to be retried
+pub const DEFAULT_MAX_RETRIES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SolveMethod
 {
 	GraphOnly,
 	GraphElimination,
-	AStar
+	AStar,
+	Bfs,
+	Dijkstra,
+	WallFollower,
+}
+
+impl SolveMethod
+{
+	/// Get the method following this one, wrapping around after the last
+	pub fn next(&self) -> SolveMethod
+	{
+		match self
+		{
+			SolveMethod::GraphOnly => SolveMethod::GraphElimination,
+			SolveMethod::GraphElimination => SolveMethod::AStar,
+			SolveMethod::AStar => SolveMethod::Bfs,
+			SolveMethod::Bfs => SolveMethod::Dijkstra,
+			SolveMethod::Dijkstra => SolveMethod::WallFollower,
+			SolveMethod::WallFollower => SolveMethod::GraphOnly,
+		}
+	}
 }
 
 impl FromStr for SolveMethod
@@ -22,10 +45,55 @@ impl FromStr for SolveMethod
     type Err = ();
 
     fn from_str(input: &str) -> Result<SolveMethod, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "graphonly"         => Ok(SolveMethod::GraphOnly),
+            "graphelimination"  => Ok(SolveMethod::GraphElimination),
+            "astar"             => Ok(SolveMethod::AStar),
+            "bfs"               => Ok(SolveMethod::Bfs),
+            "dijkstra"          => Ok(SolveMethod::Dijkstra),
+            "wallfollower"      => Ok(SolveMethod::WallFollower),
+            _                   => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for SolveMethod
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		let name = match self
+		{
+			SolveMethod::GraphOnly => "GraphOnly",
+			SolveMethod::GraphElimination => "GraphElimination",
+			SolveMethod::AStar => "AStar",
+			SolveMethod::Bfs => "Bfs",
+			SolveMethod::Dijkstra => "Dijkstra",
+			SolveMethod::WallFollower => "WallFollower",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+/// Algorithms for generating a maze
+#[derive(Debug, Clone, Copy)]
+pub enum GenMethod
+{
+	Backtracker,
+	Prim,
+	Kruskal,
+	AldousBroder,
+}
+
+impl FromStr for GenMethod
+{
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<GenMethod, Self::Err> {
         match input {
-            "GraphOnly"         => Ok(SolveMethod::GraphOnly),
-            "GraphElimination"  => Ok(SolveMethod::GraphElimination),
-            "AStar"             => Ok(SolveMethod::AStar),
+            "backtracker"       => Ok(GenMethod::Backtracker),
+            "prim"              => Ok(GenMethod::Prim),
+            "kruskal"           => Ok(GenMethod::Kruskal),
+            "aldous-broder"     => Ok(GenMethod::AldousBroder),
             _                   => Err(()),
         }
     }
@@ -35,8 +103,29 @@ impl FromStr for SolveMethod
 #[derive(Debug)]
 pub enum Job
 {
-	GenerateMaze(Dimensions),
+	GenerateMaze(Dimensions, GenMethod),
+	/// Carve one passage of a maze, starting a new one if none is being generated
+	GenerateStep(Dimensions, GenMethod),
+	/// Generate a maze and solve it, if generating succeeded
+	GenerateAndSolve(Dimensions, GenMethod, SolveMethod),
+	LoadMaze(String),
+	ValidateMaze,
+	ShowStats,
 	SolveMaze(SolveMethod),
+	SolveStep,
+	ClearSolution,
+	SetEndpoints(usize, usize),
+	SetMaxRetries(usize),
+	SetBraid(f32),
+	SetHard(bool),
+	SetThreads(usize),
+	SetSeed(u64),
+	SaveMaze(String),
+	ExportPng(String),
+	ExportDot(String),
+	/// The jobs requested on the command line have been sent, later jobs come from the user
+	StartInteractive,
+	Cancel,
 	Quit
 }
 
@@ -47,6 +136,9 @@ pub enum UIRequest
 	ShowError(String),
 	ShowInfo(String),
 	ShowMaze(Arc<Mutex<Maze>>),
+	/// Show a maze being solved, with the method used and whether a route was found
+	ShowMazeWithMeta { maze: Arc<Mutex<Maze>>, solved: bool, method: Option<SolveMethod> },
+	ShowProgress(f32),
 	Quit,
 }
 
@@ -92,6 +184,22 @@ impl From<IOError> for AppError
 	}
 }
 
+impl From<fmt::Error> for AppError
+{
+	fn from(err: fmt::Error) -> AppError
+	{
+		AppError::new(&format!("Error formatting text: {}", err))
+	}
+}
+
+impl<T> From<PoisonError<T>> for AppError
+{
+	fn from(_err: PoisonError<T>) -> AppError
+	{
+		AppError::new("Shared data is unusable after a thread panicked while holding its lock")
+	}
+}
+
 impl From<ParseIntError> for AppError
 {
 	fn from(err: ParseIntError) -> AppError
@@ -100,3 +208,202 @@ impl From<ParseIntError> for AppError
 	}
 }
 
+
+impl From<serde_json::Error> for AppError
+{
+	fn from(err: serde_json::Error) -> AppError
+	{
+		AppError::new(&err.to_string())
+	}
+}
+
+impl From<png::EncodingError> for AppError
+{
+	fn from(err: png::EncodingError) -> AppError
+	{
+		AppError::new(&err.to_string())
+	}
+}
+
+/// Run an operation until it succeeds or the retry budget is exhausted
+///
+/// # Parameters
+///
+/// * `max_retries`     - Maximum number of attempts
+/// * `constraint`      - What the operation tries to achieve, used in the error message
+/// * `operation`       - The operation, called with the attempt number starting from 1
+///
+/// Returns the result of the first successful attempt,
+/// or AppError if none of the attempts succeeded.
+///
+pub fn retry<T, F>(max_retries: usize, constraint: &str, operation: F) -> Result<T, AppError>
+	where F: FnMut(usize) -> Result<T, AppError>
+{
+	retry_unless(max_retries, constraint, || false, operation)
+}
+
+/// Run an operation until it succeeds, the retry budget is exhausted or it is stopped
+///
+/// # Parameters
+///
+/// * `max_retries`     - Maximum number of attempts
+/// * `constraint`      - What the operation tries to achieve, used in the error message
+/// * `stop`            - Checked after a failed attempt, true gives up without retrying
+/// * `operation`       - The operation, called with the attempt number starting from 1
+///
+/// Returns the result of the first successful attempt, the error of
+/// the attempt after which it was stopped, or AppError if none of the
+/// attempts succeeded.
+///
+pub fn retry_unless<T, S, F>(max_retries: usize, constraint: &str, stop: S, mut operation: F) -> Result<T, AppError>
+	where S: Fn() -> bool,
+	      F: FnMut(usize) -> Result<T, AppError>
+{
+	let attempts = std::cmp::max(max_retries, 1);
+
+	for attempt in 1..=attempts
+	{
+		match operation(attempt)
+		{
+			Ok(result) => return Ok(result),
+			Err(e) if stop() => return Err(e),
+			Err(e) => debug!("Attempt {} to {} failed: {}", attempt, constraint, e),
+		}
+	}
+
+	Err(AppError::new(&format!("Couldn't {} after {} attempts", constraint, attempts)))
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use std::fmt::Write;
+
+	#[test]
+	fn retry_gives_up_after_max_retries()
+	{
+		let mut count = 0;
+		let result: Result<(), AppError> = retry(3, "satisfy an impossible constraint", |_| {
+			count += 1;
+			Err(AppError::new("impossible"))
+		});
+
+		assert_eq!(count, 3);
+		let error = result.unwrap_err().to_string();
+		assert!(error.contains("satisfy an impossible constraint"));
+		assert!(error.contains("3 attempts"));
+	}
+
+	#[test]
+	fn retry_stops_at_first_success()
+	{
+		let result = retry(5, "succeed", |attempt| {
+			if attempt == 2
+			{
+				Ok(attempt)
+			}
+			else
+			{
+				Err(AppError::new("not yet"))
+			}
+		});
+
+		assert_eq!(result.unwrap(), 2);
+	}
+
+	#[test]
+	fn retry_stops_when_told_to()
+	{
+		let mut count = 0;
+		let result: Result<(), AppError> = retry_unless(5, "finish", || true, |_| {
+			count += 1;
+			Err(AppError::new("stopped"))
+		});
+
+		assert_eq!(count, 1);
+		assert_eq!(result.unwrap_err().to_string(), "Error: stopped");
+	}
+
+	#[test]
+	fn parse_solve_methods()
+	{
+		assert!(matches!(SolveMethod::from_str("GraphOnly"), Ok(SolveMethod::GraphOnly)));
+		assert!(matches!(SolveMethod::from_str("GraphElimination"), Ok(SolveMethod::GraphElimination)));
+		assert!(matches!(SolveMethod::from_str("astar"), Ok(SolveMethod::AStar)));
+		assert!(matches!(SolveMethod::from_str("BFS"), Ok(SolveMethod::Bfs)));
+		assert!(SolveMethod::from_str("Dfs").is_err());
+	}
+
+	#[test]
+	fn solve_methods_round_trip()
+	{
+		for method in [SolveMethod::GraphOnly, SolveMethod::GraphElimination, SolveMethod::AStar, SolveMethod::Bfs,
+		               SolveMethod::Dijkstra, SolveMethod::WallFollower]
+		{
+			assert_eq!(SolveMethod::from_str(&method.to_string()), Ok(method));
+		}
+	}
+
+	#[test]
+	fn next_solve_method_cycles_through_all()
+	{
+		let mut method = SolveMethod::AStar;
+		let mut seen = Vec::new();
+		for _ in 0..6
+		{
+			seen.push(method);
+			method = method.next();
+		}
+		assert_eq!(method, SolveMethod::AStar);
+		for other in [SolveMethod::GraphOnly, SolveMethod::GraphElimination, SolveMethod::Bfs,
+		              SolveMethod::Dijkstra, SolveMethod::WallFollower]
+		{
+			assert!(seen.contains(&other));
+		}
+	}
+
+	#[test]
+	fn format_error_converts_to_app_error()
+	{
+		struct Unformattable;
+
+		impl fmt::Display for Unformattable
+		{
+			fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result
+			{
+				Err(fmt::Error)
+			}
+		}
+
+		fn format_value<T: fmt::Display>(value: T) -> Result<String, AppError>
+		{
+			let mut text = String::new();
+			write!(text, "{}", value)?;
+			Ok(text)
+		}
+
+		assert_eq!(format_value(21).unwrap(), "21");
+		assert!(format_value(Unformattable).is_err());
+	}
+
+	#[test]
+	fn poisoned_lock_converts_to_app_error()
+	{
+		let shared = Arc::new(Mutex::new(0));
+		let poisoner = shared.clone();
+		let result = std::thread::spawn(move || {
+			let _guard = poisoner.lock().unwrap();
+			panic!("poison the lock");
+		}).join();
+		assert!(result.is_err());
+
+		fn read_value(shared: &Mutex<i32>) -> Result<i32, AppError>
+		{
+			let value = shared.lock()?;
+			Ok(*value)
+		}
+
+		assert!(read_value(&shared).is_err());
+	}
+}
@@ -0,0 +1,475 @@
+// Mazetool - exporting mazes to files of different formats
+
+use std::fs::File;
+use std::io::{ BufWriter, Write };
+use std::path::Path;
+
+use super::common::AppError;
+use super::maze::{ Maze, MazeCell, MazeCellType };
+
+/// Default size of one maze cell in exported images, in pixels
+pub const DEFAULT_CELL_PX: u32 = 10;
+
+const COLOR_WALL: [u8; 4] = [0, 0, 0, 255];
+const COLOR_PASSAGE: [u8; 4] = [255, 255, 255, 255];
+const COLOR_START: [u8; 4] = [0, 200, 0, 255];
+const COLOR_END: [u8; 4] = [220, 0, 0, 255];
+const COLOR_ROUTE: [u8; 4] = [0, 0, 255, 255];
+const COLOR_VISITED: [u8; 4] = [211, 211, 211, 255];
+
+/// An RGBA image, four bytes per pixel, row by row
+pub struct RgbaImage
+{
+	pub width: u32,
+	pub height: u32,
+	pub pixels: Vec<u8>,
+}
+
+impl RgbaImage
+{
+	fn new(width: u32, height: u32) -> RgbaImage
+	{
+		RgbaImage {
+			width,
+			height,
+			pixels: vec![0; (width * height * 4) as usize],
+		}
+	}
+
+	fn get_pixel(&self, x: u32, y: u32) -> [u8; 4]
+	{
+		let i = ((x + y * self.width) * 4) as usize;
+		[self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+	}
+
+	fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4])
+	{
+		let i = ((x + y * self.width) * 4) as usize;
+		self.pixels[i..i + 4].copy_from_slice(&color);
+	}
+
+	/// Save the image as a PNG file
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Target filename
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn write_png(&self, filename: &str) -> Result<(), AppError>
+	{
+		let path = Path::new(filename);
+		let file = match File::create(path)
+		{
+			Err(e) => {
+				let error = format!("Couldn't create image file {}: {}", path.display(), e);
+				return Err(AppError::new(&error));
+			},
+			Ok(file) => file,
+		};
+
+		let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+		encoder.set_color(png::ColorType::Rgba);
+		encoder.set_depth(png::BitDepth::Eight);
+		let mut writer = encoder.write_header()?;
+		writer.write_image_data(&self.pixels)?;
+
+		Ok(())
+	}
+}
+
+fn svg_color(color: [u8; 4]) -> String
+{
+	format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+fn cell_color(cell: &MazeCell) -> [u8; 4]
+{
+	match cell.celltype
+	{
+		MazeCellType::Wall => COLOR_WALL,
+		MazeCellType::Start => COLOR_START,
+		MazeCellType::End => COLOR_END,
+		MazeCellType::Passage => {
+			if cell.on_route
+			{
+				COLOR_ROUTE
+			}
+			else if cell.visited
+			{
+				COLOR_VISITED
+			}
+			else
+			{
+				COLOR_PASSAGE
+			}
+		},
+	}
+}
+
+impl Maze
+{
+	/// Save the maze in the format given by the extension of the filename
+	///
+	/// Supported extensions are `.maze` for the maze file format,
+	/// `.maze.gz` for a compressed maze file, `.json`, `.png` and `.svg`.
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Target filename
+	///
+	/// Returns AppError on failure or if the extension isn't supported.
+	///
+	pub fn save(&self, filename: &str) -> Result<(), AppError>
+	{
+		let extension = Path::new(filename)
+			.extension()
+			.and_then(|e| e.to_str())
+			.unwrap_or("")
+			.to_lowercase();
+
+		if Maze::is_compressed_file(filename)
+		{
+			return self.write_to_file(filename);
+		}
+
+		match extension.as_str()
+		{
+			"maze" => self.write_to_file(filename),
+			"json" => self.write_to_json(filename),
+			"png" => self.write_to_png(filename, DEFAULT_CELL_PX),
+			"svg" => self.write_to_svg(filename),
+			_ => {
+				let error = format!("Unsupported file type of {}, use .maze, .maze.gz, .json, .png or .svg", filename);
+				Err(AppError::new(&error))
+			},
+		}
+	}
+
+	/// Save the maze as JSON
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Target filename
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn write_to_json(&self, filename: &str) -> Result<(), AppError>
+	{
+		let json = self.to_json()?;
+		Maze::write_atomically(filename, "JSON file", |file| {
+			file.write_all(json.as_bytes())?;
+			Ok(())
+		})
+	}
+
+	/// Render the maze to an RGBA image
+	///
+	/// # Parameters
+	///
+	/// * `cell_px`         - Size of one maze cell in pixels
+	///
+	pub fn to_rgba(&self, cell_px: u32) -> RgbaImage
+	{
+		let cell_px = std::cmp::max(cell_px, 1);
+		let mut image = RgbaImage::new(self.dimensions.width as u32 * cell_px,
+		                               self.dimensions.height as u32 * cell_px);
+
+		for y in 0..image.height
+		{
+			for x in 0..image.width
+			{
+				let position = (x / cell_px) as usize + (y / cell_px) as usize * self.dimensions.width;
+				image.set_pixel(x, y, cell_color(&self.cells[position]));
+			}
+		}
+
+		image
+	}
+
+	/// Save the maze as a PNG image
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Target filename
+	/// * `cell_px`         - Size of one maze cell in pixels
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn write_to_png(&self, filename: &str, cell_px: u32) -> Result<(), AppError>
+	{
+		self.to_rgba(cell_px).write_png(filename)
+	}
+
+	/// Save the maze as an SVG image
+	///
+	/// Each cell is one unit in the SVG coordinates. Walls, the start and
+	/// the end are drawn as rectangles and a solved route as a polyline.
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Target filename
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn write_to_svg(&self, filename: &str) -> Result<(), AppError>
+	{
+		let width = self.dimensions.width;
+		let height = self.dimensions.height;
+
+		Maze::write_atomically(filename, "SVG image", |file| {
+			writeln!(file, "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">",
+			         width, height)?;
+			writeln!(file, "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+			         width, height, svg_color(COLOR_PASSAGE))?;
+
+			for (position, cell) in self.cells.iter().enumerate()
+			{
+				let color = match cell.celltype
+				{
+					MazeCellType::Wall => COLOR_WALL,
+					MazeCellType::Start => COLOR_START,
+					MazeCellType::End => COLOR_END,
+					MazeCellType::Passage => continue,
+				};
+				writeln!(file, "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{}\"/>",
+				         position % width, position / width, svg_color(color))?;
+			}
+
+			if let Some(route) = self.route_positions()
+			{
+				let points: Vec<String> = route.iter()
+					.map(|p| format!("{}.5,{}.5", p % width, p / width))
+					.collect();
+				writeln!(file, "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"0.5\"/>",
+				         points.join(" "), svg_color(COLOR_ROUTE))?;
+			}
+
+			writeln!(file, "</svg>")?;
+			Ok(())
+		})
+	}
+
+	/// Get the topology graph of the maze in the Graphviz DOT format
+	///
+	/// The graph is the simplified one of `graph_nodes` and `graph_edges`,
+	/// with a node for the start, the end, every intersection and every
+	/// dead end. Every node is labeled with its coordinates, and the start
+	/// and the end are drawn with double circles. Every corridor between
+	/// two nodes is one edge without an arrow, labeled with its length.
+	///
+	/// The graph has no nodes if the topology graph has not been created.
+	pub fn to_dot(&self) -> String
+	{
+		let width = self.dimensions.width;
+		let mut dot = String::from("digraph maze {\n\tedge [dir=none];\n");
+
+		for position in self.graph_nodes()
+		{
+			let shape = if position == self.start || position == self.end { "doublecircle" } else { "circle" };
+			dot.push_str(&format!("\tn{} [label=\"{},{}\", shape={}];\n",
+			                      position, position % width, position / width, shape));
+		}
+
+		for (a, b, length) in self.graph_edges()
+		{
+			dot.push_str(&format!("\tn{} -> n{} [label=\"{}\"];\n", a, b, length));
+		}
+
+		dot.push_str("}\n");
+		dot
+	}
+
+	/// Save the topology graph of the maze in the Graphviz DOT format
+	///
+	/// The topology graph is created first, if it doesn't exist yet.
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Target filename
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn write_to_dot(&mut self, filename: &str) -> Result<(), AppError>
+	{
+		if !self.graph_created
+		{
+			self.create_topology_graph();
+		}
+
+		let dot = self.to_dot();
+		Maze::write_atomically(filename, "graph file", |file| {
+			file.write_all(dot.as_bytes())?;
+			Ok(())
+		})
+	}
+
+	/// Export a horizontal strip of thumbnails to a PNG file
+	///
+	/// Useful for showing the stages of generating or solving a maze
+	/// side by side.
+	///
+	/// # Parameters
+	///
+	/// * `frames`          - Mazes to render, from left to right
+	/// * `filename`        - Target filename
+	/// * `thumb_px`        - Width and height of one thumbnail in pixels
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn export_stage_strip(frames: &[Maze], filename: &str, thumb_px: u32) -> Result<(), AppError>
+	{
+		if frames.is_empty() || thumb_px == 0
+		{
+			return Err(AppError::new("Nothing to export in the stage strip"));
+		}
+
+		let mut strip = RgbaImage::new(thumb_px * frames.len() as u32, thumb_px);
+
+		for (i, frame) in frames.iter().enumerate()
+		{
+			let image = frame.to_rgba(1);
+			let offset = i as u32 * thumb_px;
+
+			// nearest neighbour scaling to the thumbnail size
+			for y in 0..thumb_px
+			{
+				for x in 0..thumb_px
+				{
+					let color = image.get_pixel(x * image.width / thumb_px, y * image.height / thumb_px);
+					strip.set_pixel(offset + x, y, color);
+				}
+			}
+		}
+
+		strip.write_png(filename)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use super::super::maze::Dimensions;
+	use super::super::common::GenMethod;
+
+	fn temp_filename(name: &str) -> String
+	{
+		let mut path = std::env::temp_dir();
+		path.push(format!("mazetool_{}_{}", std::process::id(), name));
+		path.to_str().unwrap().to_string()
+	}
+
+	#[test]
+	fn png_has_maze_dimensions()
+	{
+		let filename = temp_filename("maze.png");
+		let mut maze = Maze::new();
+		maze.reset(Dimensions { width: 21, height: 11 }).unwrap();
+
+		maze.write_to_png(&filename, 4).unwrap();
+
+		assert!(std::fs::metadata(&filename).unwrap().len() > 0);
+		let decoder = png::Decoder::new(File::open(&filename).unwrap());
+		let reader = decoder.read_info().unwrap();
+		assert_eq!(reader.info().width, 21 * 4);
+		assert_eq!(reader.info().height, 11 * 4);
+		std::fs::remove_file(&filename).unwrap();
+	}
+
+	#[test]
+	fn svg_contains_walls()
+	{
+		let filename = temp_filename("maze.svg");
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 11, height: 11 }, GenMethod::Backtracker).unwrap();
+		maze.run_bfs(false);
+
+		maze.write_to_svg(&filename).unwrap();
+
+		let svg = std::fs::read_to_string(&filename).unwrap();
+		assert!(svg.starts_with("<svg"));
+		assert!(svg.contains("viewBox=\"0 0 11 11\""));
+		assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"1\" height=\"1\" fill=\"#000000\"/>"));
+		assert!(svg.contains("<polyline"));
+		std::fs::remove_file(&filename).unwrap();
+	}
+
+	#[test]
+	fn save_by_extension()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 11, height: 11 }, GenMethod::Prim).unwrap();
+
+		let signatures: [(&str, &[u8]); 6] = [
+			("maze", b"Maze 11 11\n"),
+			("maze.gz", b"\x1f\x8b"),
+			("MAZE", b"Maze 11 11\n"),
+			("json", b"{"),
+			("png", b"\x89PNG"),
+			("svg", b"<svg"),
+		];
+		for (extension, signature) in signatures.iter()
+		{
+			let filename = temp_filename(&format!("saved.{}", extension));
+			maze.save(&filename).unwrap();
+			let contents = std::fs::read(&filename).unwrap();
+			std::fs::remove_file(&filename).unwrap();
+			assert!(contents.starts_with(signature), "wrong format for .{}", extension);
+		}
+
+		for filename in ["saved.txt", "saved", "saved.gz"]
+		{
+			let filename = temp_filename(filename);
+			let error = maze.save(&filename).unwrap_err().to_string();
+			assert!(error.contains(".maze, .maze.gz, .json, .png or .svg"));
+			assert!(std::fs::metadata(&filename).is_err());
+		}
+	}
+
+	#[test]
+	fn topology_graph_as_dot()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Prim).unwrap();
+		assert!(!maze.to_dot().contains("->"));
+
+		let filename = temp_filename("graph.dot");
+		maze.write_to_dot(&filename).unwrap();
+		let dot = std::fs::read_to_string(&filename).unwrap();
+		std::fs::remove_file(&filename).unwrap();
+
+		assert!(maze.graph_created);
+		assert_eq!(dot, maze.to_dot());
+		assert!(dot.starts_with("digraph maze {"));
+		assert!(dot.ends_with("}\n"));
+
+		let nodes = maze.graph_nodes();
+		let edges = maze.graph_edges();
+		assert!(!edges.is_empty());
+		assert_eq!(dot.matches(" [label=\"").count(), nodes.len() + edges.len());
+		assert_eq!(dot.matches(" -> ").count(), edges.len());
+		let (a, b, length) = edges[0];
+		assert!(dot.contains(&format!("\tn{} -> n{} [label=\"{}\"];", a, b, length)));
+		// corners of a corridor are not nodes
+		let linked_cells = maze.cells.iter().filter(|c| c.nodes.iter().flatten().count() > 0).count();
+		assert!(nodes.len() < linked_cells);
+		let start = format!("\tn{} [label=\"{},0\", shape=doublecircle];", maze.start, maze.start);
+		assert!(dot.contains(&start));
+	}
+
+	#[test]
+	fn stage_strip_is_three_thumbnails_wide()
+	{
+		let filename = &temp_filename("strip.png");
+		let frames = vec![Maze::new(), Maze::new(), Maze::new()];
+
+		Maze::export_stage_strip(&frames, filename, 32).unwrap();
+
+		let decoder = png::Decoder::new(File::open(filename).unwrap());
+		let reader = decoder.read_info().unwrap();
+		assert_eq!(reader.info().width, 3 * 32);
+		assert_eq!(reader.info().height, 32);
+		std::fs::remove_file(filename).unwrap();
+	}
+}
@@ -1,26 +1,142 @@
 // Mazetool - graphical user interface with ggez
 
 use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crossbeam::channel::{Receiver, Sender};
 use ggez::event;
-use ggez::event::winit_event::{Event, KeyboardInput, WindowEvent};
+use ggez::event::winit_event::{ElementState, Event, KeyboardInput, MouseButton, WindowEvent};
 use ggez::graphics::{self, Color, Rect};
 use ggez::{Context, GameResult};
 use winit::event_loop::ControlFlow;
 use glam::*;
 
 use super::userinterface::UserInterface;
-use super::common::{ UIRequest, Job };
-use super::maze::{ Maze, MazeCellType };
+use super::common::{ UIRequest, Job, AppError, GenMethod, SolveMethod };
+use super::maze::{ Dimensions, Maze, MazeCellType };
+use super::export::DEFAULT_CELL_PX;
+use super::theme::Theme;
+
+/// Change of the zoom level for one key press
+const ZOOM_STEP: f32 = 1.25;
+/// Largest zoom level, relative to the maze fitted to the window
+const ZOOM_MAX: f32 = 32.0;
+/// Distance the view moves for one key press, in pixels
+const PAN_STEP_PX: f32 = 100.0;
+/// Part of the maze that is kept on the screen when panning, in pixels
+const VIEW_MARGIN_PX: f32 = 50.0;
+
+/// Compute the size of one maze block and the offsets centering the maze on the screen
+///
+/// Blocks are square, so a maze with a different aspect ratio than the
+/// screen is letterboxed.
+///
+/// Returns a tuple of block size, x offset and y offset.
+fn compute_layout(screen_w: f32, screen_h: f32, dimensions: Dimensions) -> (f32, f32, f32)
+{
+	let block_size = (std::cmp::min(screen_h as usize / dimensions.height,
+	                                screen_w as usize / dimensions.width)) as f32;
+	let offset_x = (screen_w - block_size * dimensions.width as f32) / 2.0;
+	let offset_y = (screen_h - block_size * dimensions.height as f32) / 2.0;
+
+	(block_size, offset_x, offset_y)
+}
+
+/// Find the maze cell at the given screen coordinates
+///
+/// # Parameters
+///
+/// * `x`               - Screen x coordinate
+/// * `y`               - Screen y coordinate
+/// * `block_size`      - Size of one maze block on the screen
+/// * `offset`          - Screen position of the top left corner of the maze
+/// * `dimensions`      - Dimensions of the maze
+///
+/// Returns the position of the cell or None if the coordinates are outside the maze.
+///
+fn position_at(x: f32, y: f32, block_size: f32, offset: Vec2, dimensions: Dimensions) -> Option<usize>
+{
+	if block_size <= 0.0 || x < offset.x || y < offset.y
+	{
+		return None;
+	}
+
+	let cell_x = ((x - offset.x) / block_size) as usize;
+	let cell_y = ((y - offset.y) / block_size) as usize;
+	if cell_x >= dimensions.width || cell_y >= dimensions.height
+	{
+		return None;
+	}
+
+	Some(cell_x + cell_y * dimensions.width)
+}
+
+/// Zoom and pan a layout that fits the maze to the screen
+///
+/// The maze is zoomed around the center of the screen and then moved by the pan.
+///
+/// # Parameters
+///
+/// * `screen`          - Width and height of the screen
+/// * `block_size`      - Size of one maze block when the maze fits the screen
+/// * `offset`          - Screen position of the maze when it fits the screen
+/// * `zoom`            - Zoom level, 1.0 for fitting the screen
+/// * `pan`             - Movement of the zoomed maze on the screen
+///
+/// Returns a tuple of the zoomed block size and the screen position of the maze.
+///
+fn apply_view(screen: Vec2, block_size: f32, offset: Vec2, zoom: f32, pan: Vec2) -> (f32, Vec2)
+{
+	let center = screen / 2.0;
+	(block_size * zoom, center + (offset - center) * zoom + pan)
+}
+
+/// Limit the pan so that a part of the maze always stays on the screen
+///
+/// # Parameters
+///
+/// * `screen`          - Width and height of the screen
+/// * `maze_size`       - Width and height of the zoomed maze on the screen
+/// * `offset`          - Screen position of the zoomed maze before panning
+/// * `pan`             - Movement of the zoomed maze on the screen
+///
+/// Returns the limited pan.
+///
+fn clamp_pan(screen: Vec2, maze_size: Vec2, offset: Vec2, pan: Vec2) -> Vec2
+{
+	let min = Vec2::splat(VIEW_MARGIN_PX) - maze_size - offset;
+	let max = screen - Vec2::splat(VIEW_MARGIN_PX) - offset;
+
+	Vec2::new(pan.x.min(max.x).max(min.x), pan.y.min(max.y).max(min.y))
+}
+
+/// Create a filename for a screenshot taken at the given time
+///
+/// # Parameters
+///
+/// * `seconds`         - Seconds since the Unix epoch
+///
+fn screenshot_filename(seconds: u64) -> String
+{
+	format!("mazetool-{}.png", seconds)
+}
 
 struct ShowMazeState
 {
 	maze: Arc<Mutex<Maze>>,
 	screen: Rect,
 	block_size: f32,
+	offset: Vec2,
+	zoom: f32,
+	pan: Vec2,
+	shown_dimensions: Option<Dimensions>,
 	error_text: Option<String>,
+	info_text: Option<String>,
+	solve_method: Option<SolveMethod>,
+	solved: bool,
 	show_distances: bool,
+	progress: Option<f32>,
+	theme: Theme,
 }
 
 impl ShowMazeState
@@ -32,8 +148,17 @@ impl ShowMazeState
 			maze: Arc::new(Mutex::new(Maze::new())), // this is replaced later by real data from Control
 			screen: Rect { x: 0.0, y: 0.0, w: 0.0 , h: 0.0},
 			block_size: 0.0,
+			offset: Vec2::new(0.0, 0.0),
+			zoom: 1.0,
+			pan: Vec2::new(0.0, 0.0),
+			shown_dimensions: None,
 			error_text: None,
+			info_text: None,
+			solve_method: None,
+			solved: false,
 			show_distances: false,
+			progress: None,
+			theme: Theme::default(),
 		};
 		Ok(s)
 	}
@@ -44,14 +169,121 @@ impl ShowMazeState
 
 		if let Ok(m) = self.maze.lock()
 		{
-			self.block_size = (std::cmp::min(self.screen.h as usize / m.dimensions.height,
-			                                 self.screen.w as usize / m.dimensions.width)) as f32;
+			let (block_size, offset_x, offset_y) = compute_layout(self.screen.w,
+			                                                      self.screen.h,
+			                                                      m.dimensions);
+			self.block_size = block_size;
+			self.offset = Vec2::new(offset_x, offset_y);
+		}
+	}
+
+	/// Get the size of one maze block and the position of the maze on the screen
+	///
+	/// The zoom and the pan are applied on top of the layout fitting
+	/// the maze to the screen.
+	///
+	fn view(&self) -> (f32, Vec2)
+	{
+		apply_view(Vec2::new(self.screen.w, self.screen.h), self.block_size, self.offset, self.zoom, self.pan)
+	}
+
+	/// Zoom the view in or out, but not out of the layout fitting the screen
+	///
+	/// # Parameters
+	///
+	/// * `factor`          - Change of the zoom level
+	///
+	fn zoom_by(&mut self, factor: f32)
+	{
+		self.zoom = (self.zoom * factor).max(1.0).min(ZOOM_MAX);
+		self.pan = self.pan * factor;
+		self.clamp_view();
+	}
+
+	/// Move the maze on the screen
+	///
+	/// # Parameters
+	///
+	/// * `x`               - Horizontal movement in pixels
+	/// * `y`               - Vertical movement in pixels
+	///
+	fn pan_by(&mut self, x: f32, y: f32)
+	{
+		self.pan = self.pan + Vec2::new(x, y);
+		self.clamp_view();
+	}
+
+	fn clamp_view(&mut self)
+	{
+		if let Some(dimensions) = self.dimensions()
+		{
+			let screen = Vec2::new(self.screen.w, self.screen.h);
+			let (block_size, offset) = apply_view(screen, self.block_size, self.offset, self.zoom, Vec2::ZERO);
+			let maze_size = Vec2::new(dimensions.width as f32, dimensions.height as f32) * block_size;
+			self.pan = clamp_pan(screen, maze_size, offset, self.pan);
 		}
 	}
 
+	/// Show the whole maze fitted to the screen
+	fn reset_view(&mut self)
+	{
+		self.zoom = 1.0;
+		self.pan = Vec2::ZERO;
+	}
+
+	/// Find the passage cell at the given screen coordinates
+	///
+	/// Returns None for walls and coordinates outside the maze.
+	///
+	fn passage_at(&self, x: f32, y: f32) -> Option<usize>
+	{
+		let (block_size, offset) = self.view();
+		let m = self.maze.lock().ok()?;
+		let position = position_at(x, y, block_size, offset, m.dimensions)?;
+		if m.cells[position].celltype == MazeCellType::Wall
+		{
+			return None;
+		}
+
+		Some(position)
+	}
+
+	/// Get the dimensions of the shown maze
+	fn dimensions(&self) -> Option<Dimensions>
+	{
+		self.maze.lock().ok().map(|m| m.dimensions)
+	}
+
+	/// Show the given maze
+	///
+	/// The view is fitted to the screen again if the size of the maze changed.
+	///
 	fn set_maze(&mut self, maze: Arc<Mutex<Maze>>)
 	{
 		self.maze = maze.clone();
+		let dimensions = self.dimensions();
+		if dimensions != self.shown_dimensions
+		{
+			self.shown_dimensions = dimensions;
+			self.reset_view();
+		}
+	}
+
+	/// Save the shown maze as a PNG image named after the current time
+	///
+	/// Returns the filename of the saved image.
+	///
+	fn save_screenshot(&self) -> Result<String, AppError>
+	{
+		let seconds = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|d| d.as_secs())
+			.unwrap_or(0);
+		let filename = screenshot_filename(seconds);
+		let m = self.maze.lock()?;
+		m.write_to_png(&filename, DEFAULT_CELL_PX)?;
+
+		Ok(filename)
 	}
 
 	fn set_show_distances(&mut self, show_distances: bool)
@@ -65,7 +297,7 @@ impl ShowMazeState
 		text.set_font(graphics::Font::default(), graphics::PxScale::from(24.0));
 		let params = graphics::DrawParam::default()
 			.dest([pos_x, pos_y])
-			.color(graphics::Color::YELLOW);
+			.color(Color::from(self.theme.text));
 
 		graphics::draw(ctx, &text, params).expect("Error drawing text");
 	}
@@ -80,27 +312,36 @@ impl event::EventHandler<ggez::GameError> for ShowMazeState
 
 	fn draw(&mut self, ctx: &mut Context) -> GameResult
 	{
-		let rect = graphics::Rect::new(0.0, 0.0, self.block_size, self.block_size);
+		let (block_size, offset) = self.view();
+		let rect = graphics::Rect::new(0.0, 0.0, block_size, block_size);
 		let wall = graphics::Mesh::new_rectangle(ctx,
 		                                         graphics::DrawMode::fill(),
 		                                         rect,
-		                                         Color::WHITE)?;
+		                                         Color::from(self.theme.wall))?;
 		let route = graphics::Mesh::new_rectangle(ctx,
 		                                          graphics::DrawMode::fill(),
 		                                          rect,
-		                                          Color::GREEN)?;
+		                                          Color::from(self.theme.route))?;
 		let visited = graphics::Mesh::new_rectangle(ctx,
 		                                            graphics::DrawMode::fill(),
 		                                            rect,
-		                                            Color {r: 0.0, g: 0.5, b: 0.5, a: 1.0 })?;
+		                                            Color::from(self.theme.visited))?;
+		let start = graphics::Mesh::new_rectangle(ctx,
+		                                          graphics::DrawMode::fill(),
+		                                          rect,
+		                                          Color::from(self.theme.start))?;
+		let end = graphics::Mesh::new_rectangle(ctx,
+		                                        graphics::DrawMode::fill(),
+		                                        rect,
+		                                        Color::from(self.theme.end))?;
 		let node = graphics::Mesh::new_circle(ctx,
 		                                      graphics::DrawMode::fill(),
 		                                      Vec2::new(0.0, 0.0),
-		                                      self.block_size / 3.0,
+		                                      block_size / 3.0,
 		                                      2.0,
-		                                      Color::GREEN)?;
+		                                      Color::from(self.theme.graph))?;
 
-		graphics::clear(ctx, [0.1, 0.2, 0.3, 1.0].into());
+		graphics::clear(ctx, self.theme.background.into());
 
 		if let Ok(m) = self.maze.lock()
 		{
@@ -108,9 +349,18 @@ impl event::EventHandler<ggez::GameError> for ShowMazeState
 			{
 				for y in 0..m.dimensions.height
 				{
-					let cell = &m.cells[x + (y * m.dimensions.width)];
-					let pos_x = x as f32 * self.block_size;
-					let pos_y = y as f32 * self.block_size;
+					let cell = match m.get(x, y)
+					{
+						Some(cell) => cell,
+						None => continue,
+					};
+					let pos_x = offset.x + x as f32 * block_size;
+					let pos_y = offset.y + y as f32 * block_size;
+					if pos_x + block_size < 0.0 || pos_y + block_size < 0.0 ||
+					   pos_x > self.screen.w || pos_y > self.screen.h
+					{
+						continue;
+					}
 
 					// draw maze walls
 					if cell.celltype == MazeCellType::Wall
@@ -125,9 +375,19 @@ impl event::EventHandler<ggez::GameError> for ShowMazeState
 					{
 						graphics::draw(ctx, &visited, (Vec2::new(pos_x, pos_y),))?;
 					}
+
+					// draw the start and the end over the route
+					if cell.celltype == MazeCellType::Start
+					{
+						graphics::draw(ctx, &start, (Vec2::new(pos_x, pos_y),))?;
+					}
+					else if cell.celltype == MazeCellType::End
+					{
+						graphics::draw(ctx, &end, (Vec2::new(pos_x, pos_y),))?;
+					}
 					if self.show_distances && (cell.celltype == MazeCellType::Passage)
 					{
-						self.draw_text(ctx, &cell.text, pos_x, pos_y);
+						self.draw_text(ctx, &cell.text(), pos_x, pos_y);
 					}
 
 					// draw maze topology graph nodes
@@ -135,8 +395,8 @@ impl event::EventHandler<ggez::GameError> for ShowMazeState
 					{
 						if let Some(_) = cell.nodes[i]
 						{
-							graphics::draw(ctx, &node, (Vec2::new(pos_x + self.block_size / 2.0,
-							                                      pos_y + self.block_size / 2.0),))?;
+							graphics::draw(ctx, &node, (Vec2::new(pos_x + block_size / 2.0,
+							                                      pos_y + block_size / 2.0),))?;
 							break;
 						}
 					}
@@ -149,15 +409,15 @@ impl event::EventHandler<ggez::GameError> for ShowMazeState
 				for (px, py, x, y, _cell) in m.into_iter()
 				{
 					debug!("Maze graph iterator returned x = {}, y = {}", x, y);
-					let pos_x = x as f32 * self.block_size + (self.block_size / 2.0);
-					let pos_y = y as f32 * self.block_size + (self.block_size / 2.0);
-					let prev_x = px as f32 * self.block_size + (self.block_size / 2.0);
-					let prev_y = py as f32 * self.block_size + (self.block_size / 2.0);
+					let pos_x = offset.x + x as f32 * block_size + (block_size / 2.0);
+					let pos_y = offset.y + y as f32 * block_size + (block_size / 2.0);
+					let prev_x = offset.x + px as f32 * block_size + (block_size / 2.0);
+					let prev_y = offset.y + py as f32 * block_size + (block_size / 2.0);
 
 					if (prev_x != pos_x) || (prev_y != pos_y)
 					{
 						let points = &[Vec2::new(prev_x, prev_y), Vec2::new(pos_x, pos_y)];
-						let mut line_width = self.block_size / 10.0;
+						let mut line_width = block_size / 10.0;
 						if line_width < 0.6
 						{
 							line_width = 0.6;
@@ -165,7 +425,7 @@ impl event::EventHandler<ggez::GameError> for ShowMazeState
 						let connection = graphics::Mesh::new_line(ctx,
 						                                          points,
 						                                          line_width,
-						                                          Color::GREEN)?;
+						                                          Color::from(self.theme.graph))?;
 						graphics::draw(ctx, &connection, (Vec2::new(0.0, 0.0),))?;
 					}
 					else
@@ -176,6 +436,33 @@ impl event::EventHandler<ggez::GameError> for ShowMazeState
 			}
 		}
 
+		// draw a progress bar at the bottom while generating or solving
+		if let Some(percent) = self.progress
+		{
+			let bar = graphics::Rect::new(0.0,
+			                              self.screen.h - 10.0,
+			                              self.screen.w * percent / 100.0,
+			                              10.0);
+			let progress = graphics::Mesh::new_rectangle(ctx,
+			                                             graphics::DrawMode::fill(),
+			                                             bar,
+			                                             Color::from(self.theme.text))?;
+			graphics::draw(ctx, &progress, (Vec2::new(0.0, 0.0),))?;
+		}
+
+		// draw info text, if any
+		if let Some(info_str) = &self.info_text
+		{
+			self.draw_text(ctx, info_str, 10.0, 10.0);
+		}
+
+		// draw the solving method, if any
+		if let Some(method) = self.solve_method
+		{
+			let status = if self.solved { " (solved)" } else { "" };
+			self.draw_text(ctx, &format!("Solver: {}{}", method, status), 10.0, self.screen.h - 40.0);
+		}
+
 		// draw error text, if any
 		if let Some(error_str) = &self.error_text
 		{
@@ -205,9 +492,48 @@ impl event::EventHandler<ggez::GameError> for ShowMazeState
 /// Graphical user interface for Mazetool
 pub struct GraphicalInterface
 {
-	#[allow(dead_code)]
 	tx: Sender<Job>,
 	rx: Receiver<UIRequest>,
+	window_size: Option<(f32, f32)>,
+	gen_method: GenMethod,
+	theme: Theme,
+}
+
+impl GraphicalInterface
+{
+	/// Run in a window instead of fullscreen
+	///
+	/// # Parameters
+	///
+	/// * `width`           - Width of the window in pixels
+	/// * `height`          - Height of the window in pixels
+	///
+	pub fn set_windowed(&mut self, width: f32, height: f32)
+	{
+		self.window_size = Some((width, height));
+	}
+
+	/// Set the algorithm used for generating new mazes from the GUI
+	///
+	/// # Parameters
+	///
+	/// * `method`          - The algorithm used for generating mazes
+	///
+	pub fn set_gen_method(&mut self, method: GenMethod)
+	{
+		self.gen_method = method;
+	}
+
+	/// Set the colors used for drawing
+	///
+	/// # Parameters
+	///
+	/// * `theme`           - Colors of the maze, the route and the texts
+	///
+	pub fn set_theme(&mut self, theme: Theme)
+	{
+		self.theme = theme;
+	}
 }
 
 impl UserInterface for GraphicalInterface
@@ -219,14 +545,27 @@ impl UserInterface for GraphicalInterface
 		{
 			tx: tx,
 			rx: rx,
+			window_size: None,
+			gen_method: GenMethod::Backtracker,
+			theme: Theme::default(),
 		}
 	}
 
 	fn run(&mut self, show_distances: bool)
 	{
-		let window_mode = ggez::conf::WindowMode::default()
-			.dimensions(1920.0, 1080.0)
-			.fullscreen_type(ggez::conf::FullscreenType::True);
+		let window_mode = match self.window_size
+		{
+			Some((width, height)) => {
+				ggez::conf::WindowMode::default()
+					.dimensions(width, height)
+					.fullscreen_type(ggez::conf::FullscreenType::Windowed)
+			},
+			None => {
+				ggez::conf::WindowMode::default()
+					.dimensions(1920.0, 1080.0)
+					.fullscreen_type(ggez::conf::FullscreenType::True)
+			},
+		};
 
 		let window_setup = ggez::conf::WindowSetup {
                                title: "Mazetool".to_owned(),
@@ -242,7 +581,13 @@ impl UserInterface for GraphicalInterface
 	    
 		let (mut ctx, event_loop) = cb.build().unwrap();
 		let mut state = ShowMazeState::new().unwrap();
+		state.theme = self.theme;
 		let rx_clone = self.rx.clone();
+		let tx_clone = self.tx.clone();
+		let gen_method = self.gen_method;
+		let mut solve_method = SolveMethod::AStar;
+		let mut cursor = Vec2::new(0.0, 0.0);
+		let mut selected_start: Option<usize> = None;
 		let screen = ggez::graphics::screen_coordinates(&ctx);
 
 		// Handle events. Refer to `winit` docs for more information.
@@ -255,19 +600,48 @@ impl UserInterface for GraphicalInterface
 				*control_flow = ControlFlow::Exit;
 			}
 
-			if let Ok(request) = rx_clone.try_recv()
+			// handle all pending requests, so a maze loaded or generated
+			// before the event loop started is shown on the first frame
+			while let Ok(request) = rx_clone.try_recv()
 			{
-				info!("UI received request: {:?}", request);
+				if let UIRequest::ShowProgress(_) = request
+				{
+					debug!("UI received request: {:?}", request);
+				}
+				else
+				{
+					info!("UI received request: {:?}", request);
+				}
 				match request
 				{
 					UIRequest::ShowError(message) => {
 						state.error_text = Some(message);
 					},
-					UIRequest::ShowInfo(_message) => {
-						//state.error_text = Some(message);
+					UIRequest::ShowInfo(message) => {
+						state.info_text = Some(message);
 					},
 					UIRequest::ShowMaze(maze) => {
+						state.info_text = None;
+						state.solved = false;
+						state.set_maze(maze);
+						state.set_screen_size(screen);
+					},
+					UIRequest::ShowMazeWithMeta { maze, solved, method } => {
+						state.info_text = None;
+						state.solved = solved;
+						if method.is_some()
+						{
+							state.solve_method = method;
+						}
 						state.set_maze(maze);
+						state.set_screen_size(screen);
+					},
+					UIRequest::ShowProgress(percent) => {
+						state.progress = None;
+						if percent < 100.0
+						{
+							state.progress = Some(percent);
+						}
 					},
 					UIRequest::Quit => {
 						*control_flow = ControlFlow::Exit;
@@ -292,12 +666,111 @@ impl UserInterface for GraphicalInterface
 						input:
 							KeyboardInput {
 								virtual_keycode: Some(keycode),
+								state: ElementState::Pressed,
 								..
 							},
 							..
 					} => {
-						if let event::KeyCode::Escape = keycode {
-							*control_flow = ControlFlow::Exit
+						match keycode
+						{
+							event::KeyCode::Escape => {
+								tx_clone.send(Job::Cancel).unwrap_or_else(|_| return);
+							},
+							event::KeyCode::Q => {
+								*control_flow = ControlFlow::Exit
+							},
+							event::KeyCode::Space => {
+								tx_clone.send(Job::SolveStep).unwrap_or_else(|_| return);
+							},
+							event::KeyCode::R => {
+								// a new maze replaces any ongoing solving
+								selected_start = None;
+								state.progress = None;
+								if let Some(dimensions) = state.dimensions()
+								{
+									tx_clone.send(Job::GenerateMaze(dimensions, gen_method))
+										.unwrap_or_else(|_| return);
+								}
+							},
+							event::KeyCode::G => {
+								// a new maze is started if none is being generated
+								selected_start = None;
+								state.progress = None;
+								if let Some(dimensions) = state.dimensions()
+								{
+									tx_clone.send(Job::GenerateStep(dimensions, gen_method))
+										.unwrap_or_else(|_| return);
+								}
+							},
+							event::KeyCode::M => {
+								// solve the same maze again with the next method
+								solve_method = solve_method.next();
+								state.solve_method = Some(solve_method);
+								tx_clone.send(Job::ClearSolution).unwrap_or_else(|_| return);
+								tx_clone.send(Job::SolveMaze(solve_method)).unwrap_or_else(|_| return);
+							},
+							event::KeyCode::C => {
+								// show the same maze without the route
+								tx_clone.send(Job::ClearSolution).unwrap_or_else(|_| return);
+							},
+							event::KeyCode::Plus | event::KeyCode::Equals => {
+								state.zoom_by(ZOOM_STEP);
+							},
+							event::KeyCode::Minus => {
+								state.zoom_by(1.0 / ZOOM_STEP);
+							},
+							event::KeyCode::Key0 => {
+								state.reset_view();
+							},
+							event::KeyCode::Left => {
+								state.pan_by(PAN_STEP_PX, 0.0);
+							},
+							event::KeyCode::Right => {
+								state.pan_by(-PAN_STEP_PX, 0.0);
+							},
+							event::KeyCode::Up => {
+								state.pan_by(0.0, PAN_STEP_PX);
+							},
+							event::KeyCode::Down => {
+								state.pan_by(0.0, -PAN_STEP_PX);
+							},
+							event::KeyCode::P => {
+								match state.save_screenshot()
+								{
+									Ok(filename) => {
+										info!("Maze saved to {}", filename);
+										state.info_text = Some(format!("Saved {}", filename));
+									},
+									Err(e) => {
+										state.error_text = Some(format!("Error saving screenshot: {}", e));
+									},
+								}
+							},
+							_ => {},
+						}
+					}
+					WindowEvent::CursorMoved { position, .. } => {
+						cursor = Vec2::new(position.x as f32, position.y as f32);
+					}
+					WindowEvent::MouseInput
+					{
+						state: ElementState::Pressed,
+						button: MouseButton::Left,
+						..
+					} => {
+						// first click selects the start, second one the end
+						match (state.passage_at(cursor.x, cursor.y), selected_start)
+						{
+							(None, _) => {
+								info!("Ignoring click outside maze passages");
+							},
+							(Some(start), None) => {
+								selected_start = Some(start);
+							},
+							(Some(end), Some(start)) => {
+								tx_clone.send(Job::SetEndpoints(start, end)).unwrap_or_else(|_| return);
+								selected_start = None;
+							},
 						}
 					}
 					// `CloseRequested` and `KeyboardInput` events won't appear here.
@@ -323,3 +796,79 @@ impl UserInterface for GraphicalInterface
 	}
 
 }
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn wide_maze_is_centered_vertically()
+	{
+		let (block_size, offset_x, offset_y) = compute_layout(1920.0,
+		                                                      1080.0,
+		                                                      Dimensions { width: 100, height: 10 });
+		assert_eq!(block_size, 19.0);
+		assert_eq!(offset_x, 10.0);
+		assert_eq!(offset_y, 445.0);
+	}
+
+	#[test]
+	fn tall_maze_is_centered_horizontally()
+	{
+		let (block_size, offset_x, offset_y) = compute_layout(1920.0,
+		                                                      1080.0,
+		                                                      Dimensions { width: 10, height: 100 });
+		assert_eq!(block_size, 10.0);
+		assert_eq!(offset_x, 910.0);
+		assert_eq!(offset_y, 40.0);
+	}
+
+	#[test]
+	fn zoom_around_screen_center()
+	{
+		let screen = Vec2::new(1000.0, 800.0);
+		let offset = Vec2::new(300.0, 200.0);
+		assert_eq!(apply_view(screen, 10.0, offset, 1.0, Vec2::ZERO), (10.0, offset));
+
+		let (block_size, zoomed) = apply_view(screen, 10.0, offset, 2.0, Vec2::ZERO);
+		assert_eq!(block_size, 20.0);
+		assert_eq!(zoomed, Vec2::new(100.0, 0.0));
+
+		let (_, panned) = apply_view(screen, 10.0, offset, 2.0, Vec2::new(-50.0, 30.0));
+		assert_eq!(panned, Vec2::new(50.0, 30.0));
+	}
+
+	#[test]
+	fn pan_keeps_maze_on_screen()
+	{
+		let screen = Vec2::new(1000.0, 800.0);
+		let maze_size = Vec2::new(2000.0, 1600.0);
+		let offset = Vec2::new(-500.0, -400.0);
+
+		let pan = Vec2::new(100.0, -100.0);
+		assert_eq!(clamp_pan(screen, maze_size, offset, pan), pan);
+		assert_eq!(clamp_pan(screen, maze_size, offset, Vec2::new(5000.0, 5000.0)),
+		           Vec2::new(1450.0, 1150.0));
+		assert_eq!(clamp_pan(screen, maze_size, offset, Vec2::new(-5000.0, -5000.0)),
+		           Vec2::new(-1450.0, -1150.0));
+	}
+
+	#[test]
+	fn screenshot_is_named_after_time()
+	{
+		assert_eq!(screenshot_filename(1234), "mazetool-1234.png");
+	}
+
+	#[test]
+	fn click_position_to_cell()
+	{
+		let dimensions = Dimensions { width: 10, height: 5 };
+		let offset = Vec2::new(100.0, 50.0);
+		assert_eq!(position_at(100.0, 50.0, 20.0, offset, dimensions), Some(0));
+		assert_eq!(position_at(145.0, 71.0, 20.0, offset, dimensions), Some(12));
+		assert_eq!(position_at(99.0, 71.0, 20.0, offset, dimensions), None);
+		assert_eq!(position_at(300.0, 71.0, 20.0, offset, dimensions), None);
+		assert_eq!(position_at(145.0, 150.0, 20.0, offset, dimensions), None);
+	}
+}
@@ -1,22 +1,44 @@
 use std::fmt::{ Display, Formatter };
 use std::result::Result;
-use std::fs::File;
+use std::fs::{ self, File };
 use std::io::prelude::*;
 use std::io::{self, BufRead};
 use std::path::Path;
 use std::str::FromStr;
-use std::cmp::Ordering;
+use std::cmp::{ Ordering, Reverse };
+use std::collections::{ BinaryHeap, VecDeque };
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicBool, AtomicUsize, Ordering as AtomicOrdering };
+use std::time::{ Duration, Instant };
 
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use rand::prelude::*;
-use heapless::binary_heap::{ BinaryHeap, Min };
+use rayon::prelude::*;
+use serde::{ Serialize, Deserialize };
 
-use super::common::AppError;
+use super::common::{ AppError, GenMethod, SolveMethod };
 
 pub const NUM_OF_DIRECTIONS: usize = 4;
 pub const MAZE_DIMENSION_MIN: usize = 10;
 pub const MAZE_DIMENSION_MAX: usize = 10000;
 pub const MAZE_DIMENSION_DEFAULT: usize = 19;
-pub const MAX_HEAP_SIZE: usize = 128;
+/// Default limit for the number of cells in a maze, every cell
+/// takes 48 bytes of memory, so the cells take at most 1.2 GB
+pub const MAZE_CELLS_MAX: usize = 25_000_000;
+/// Largest width or height of a maze generated with Aldous-Broder,
+/// which is too slow for mazes near MAZE_DIMENSION_MAX
+pub const ALDOUS_BRODER_DIMENSION_MAX: usize = 1001;
+pub const DEFAULT_WEIGHT: u32 = 1;
+const WEIGHT_LINE_PREFIX: &str = "W ";
+/// Ending of the names of gzip-compressed maze files
+pub const COMPRESSED_MAZE_SUFFIX: &str = ".maze.gz";
+/// Smallest number of cells on one distance level worth expanding in parallel
+const PARALLEL_FRONTIER_MIN: usize = 1024;
+
+/// Number of temporary files written so far, to give each a unique name
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Clone, Copy)]
 enum GraphNodeType
@@ -25,6 +47,7 @@ enum GraphNodeType
 	Straight,
 	Intersection, // or a corner
 	DeadEnd,
+	Start,
 	End,
 }
 
@@ -35,7 +58,7 @@ struct GraphNodeInfo
 	directions: Vec<Direction>,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction
 {
 	North,
@@ -55,6 +78,24 @@ impl Direction
 		return directions;
 	}
 
+	/// Get the direction after turning right from this direction
+	pub fn turn_right(&self) -> Direction
+	{
+		match self
+		{
+			Direction::North => Direction::East,
+			Direction::East => Direction::South,
+			Direction::South => Direction::West,
+			Direction::West => Direction::North,
+		}
+	}
+
+	/// Get the direction after turning left from this direction
+	pub fn turn_left(&self) -> Direction
+	{
+		self.turn_right().get_opposite_direction()
+	}
+
 	pub fn get_opposite_direction(&self) -> Direction
 	{
 		match self
@@ -79,6 +120,53 @@ impl Direction
 		}
 	}
 
+	/// Get a single character (U, D, L or R) describing a move to this direction
+	pub fn to_move_char(self) -> char
+	{
+		match self
+		{
+			Direction::North => 'U',
+			Direction::East => 'R',
+			Direction::West => 'L',
+			Direction::South => 'D',
+		}
+	}
+
+	/// Get an arrow pointing to this direction
+	///
+	/// # Parameters
+	///
+	/// * `ascii`           - True to use one of `^v<>` instead of the arrow glyphs
+	///
+	pub fn to_arrow_char(self, ascii: bool) -> char
+	{
+		match (self, ascii)
+		{
+			(Direction::North, false) => '↑',
+			(Direction::East, false) => '→',
+			(Direction::West, false) => '←',
+			(Direction::South, false) => '↓',
+			(Direction::North, true) => '^',
+			(Direction::East, true) => '>',
+			(Direction::West, true) => '<',
+			(Direction::South, true) => 'v',
+		}
+	}
+
+	/// Get the change of the row and the column when moving to this direction
+	///
+	/// Returns the offset as `(row, column)`.
+	pub fn to_offset(self) -> (isize, isize)
+	{
+		match self
+		{
+			Direction::North => (-1, 0),
+			Direction::East => (0, 1),
+			Direction::West => (0, -1),
+			Direction::South => (1, 0),
+		}
+	}
+
 	pub fn from_usize(value: usize) -> Direction
 	{
 		match value
@@ -106,15 +194,60 @@ impl Display for Direction
 }
 
 /// Dimensions (width and height) of a maze
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Dimensions
 {
 	pub width: usize,
 	pub height: usize,
 }
 
+impl Dimensions
+{
+	/// Get dimensions suitable for generating a maze
+	///
+	/// Mazes are carved with walls on even and passages on odd coordinates,
+	/// so even dimensions are rounded up to the next odd value, or down
+	/// if that would exceed the maximum dimension.
+	pub fn normalize(&self) -> Dimensions
+	{
+		let normalize_one = |value: usize| {
+			if value % 2 == 1
+			{
+				value
+			}
+			else if value < MAZE_DIMENSION_MAX
+			{
+				value + 1
+			}
+			else
+			{
+				value - 1
+			}
+		};
+		let normalized = Dimensions {
+			width: normalize_one(self.width),
+			height: normalize_one(self.height),
+		};
+
+		if normalized.width != self.width || normalized.height != self.height
+		{
+			warn!("Maze dimensions must be odd, adjusted {} to {}", self, normalized);
+		}
+
+		normalized
+	}
+}
+
+impl Display for Dimensions
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+	{
+		write!(f, "{}x{}", self.width, self.height)
+	}
+}
+
 /// Posibble states of one cell in a maze
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum MazeCellType
 {
 	Wall,
@@ -137,33 +270,73 @@ impl Display for MazeCellType
     }
 }
 
+impl MazeCellType
+{
+	/// Get a plain ASCII character for showing the cell
+	///
+	/// Same as the `Display` glyphs, except walls are shown as '#'.
+	pub fn to_ascii_char(&self) -> char
+	{
+		match self {
+			MazeCellType::Wall => '#',
+			MazeCellType::Passage => ' ',
+			MazeCellType::Start => 'S',
+			MazeCellType::End => 'E',
+		}
+	}
+}
+
 impl FromStr for MazeCellType
 {
 	type Err = AppError;
-	fn from_str(hex_code: &str) -> Result<Self, Self::Err> {
-		//TODO: proper implementation for MazeCellType
-        let _celltype: u8 = u8::from_str_radix(&hex_code[0..1], 16)?;
-        Ok(MazeCellType::Wall)
-
-        // u8::from_str_radix(src: &str, radix: u32) converts a string
-        // slice in a given base to u8
-        //let r: u8 = u8::from_str_radix(&hex_code[1..3], 16)?;
-        //let g: u8 = u8::from_str_radix(&hex_code[3..5], 16)?;
-        //let b: u8 = u8::from_str_radix(&hex_code[5..7], 16)?;
-
-        //Ok(RGB { r, g, b })
-    }
+	fn from_str(glyph: &str) -> Result<Self, Self::Err> {
+		match glyph
+		{
+			"█" => Ok(MazeCellType::Wall),
+			" " => Ok(MazeCellType::Passage),
+			"S" => Ok(MazeCellType::Start),
+			"E" => Ok(MazeCellType::End),
+			_ => Err(AppError::new(&format!("Invalid maze cell '{}'", glyph))),
+		}
+	}
 }
 
 /// One cell of a maze
-#[derive(Debug, Clone)]
+///
+/// Cells are kept small, as a large maze has millions of them.
+/// Positions of graph nodes are stored as u32, which is enough
+/// for MAZE_CELLS_MAX cells.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MazeCell
 {
 	pub celltype: MazeCellType,
 	pub visited: bool,
 	pub on_route: bool,
-	pub nodes: [Option<usize>; NUM_OF_DIRECTIONS],
-	pub text: String,
+	pub nodes: [Option<u32>; NUM_OF_DIRECTIONS],
+	/// Distance from the start, if computed and reachable
+	#[serde(default)]
+	pub distance: Option<u32>,
+	/// Cost of moving to this cell when solving the maze
+	#[serde(default = "default_weight")]
+	pub weight: u32,
+}
+
+fn default_weight() -> u32
+{
+	DEFAULT_WEIGHT
+}
+
+impl MazeCell
+{
+	/// Get the distance from the start as text, empty if not known
+	pub fn text(&self) -> String
+	{
+		match self.distance
+		{
+			Some(d) => d.to_string(),
+			None => String::new(),
+		}
+	}
 }
 
 impl Display for MazeCell
@@ -174,8 +347,185 @@ impl Display for MazeCell
     }
 }
 
-/// The maze data structure
+/// One node in the A* open list
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+struct AStarItem
+{
+	position: usize,
+	f: usize,
+	g: usize,
+}
+
+impl Ord for AStarItem {
+	fn cmp(&self, other: &Self) -> Ordering {
+		if self.f == other.f
+		{
+			Ordering::Equal
+		}
+		else if self.f > other.f
+		{
+			Ordering::Greater
+		}
+		else
+		{
+			Ordering::Less
+		}
+	}
+}
+
+impl PartialOrd for AStarItem {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+/// State of an ongoing A* search
+#[derive(Clone)]
+struct AStarState
+{
+	open_list: BinaryHeap<Reverse<AStarItem>>,
+	costs: Vec<Option<usize>>,
+	parents: Vec<Option<usize>>,
+	/// Cost of the cheapest step, so the estimate never exceeds the real cost
+	min_cost: usize,
+}
+
+impl AStarState
+{
+	fn new(cells: usize, min_cost: usize) -> AStarState
+	{
+		AStarState {
+			open_list: BinaryHeap::new(),
+			costs: vec![None; cells],
+			parents: vec![None; cells],
+			min_cost,
+		}
+	}
+}
+
+/// State of an ongoing Dijkstra search
+#[derive(Clone)]
+struct DijkstraState
+{
+	queue: BinaryHeap<Reverse<(usize, usize)>>,
+	costs: Vec<Option<usize>>,
+	parents: Vec<Option<usize>>,
+}
+
+/// State of an ongoing breadth-first search
+#[derive(Clone)]
+struct BfsState
+{
+	queue: VecDeque<usize>,
+	parents: Vec<Option<usize>>,
+}
+
+/// State of an ongoing generation, with the data of the algorithm used
+#[derive(Clone)]
+enum GenerationState
+{
+	/// Cells and directions left to dig to, the latest last
+	Backtracker { positions: Vec<(usize, Direction)>, dug: usize },
+	/// Walls from the rooms in the maze towards rooms which may be outside of it
+	Prim { frontier: Vec<(usize, Direction)>, added: usize },
+	/// Walls between rooms in random order, the next one to try and the set of each room
+	Kruskal { edges: Vec<(usize, usize, usize)>, next: usize, sets: Vec<usize>, connected: usize },
+	/// Current room of the random walk and the number of rooms not visited yet
+	AldousBroder { position: usize, unvisited: usize },
+}
+
+/// Outcome of solving a maze
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveResult
+{
+	/// A route from the start to the end is marked
+	Solved,
+	/// The search finished without finding a route
+	NoPath,
+	/// The search was stopped after the maximum number of steps
+	TimedOut,
+}
+
+/// Measurements of the last search done by `Maze::solve`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolveStats
+{
+	/// Number of search steps taken
+	pub steps: usize,
+	/// Number of steps on the route, zero if no route was found
+	pub path_len: usize,
+	/// Number of cells visited by the search
+	pub cells_visited: usize,
+	/// Time taken by the search
+	pub elapsed: Duration,
+}
+
+impl Display for SolveStats
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+	{
+		write!(f, "{} search steps, {} cells visited, route of {} steps in {:.3} ms",
+		       self.steps, self.cells_visited, self.path_len, self.elapsed.as_secs_f64() * 1000.0)
+	}
+}
+
+/// State of an ongoing wall follower walk
 #[derive(Clone)]
+struct WallFollowerState
+{
+	route: Vec<usize>,
+	heading: Direction,
+	first_move: Option<(Direction, usize)>,
+	steps: usize,
+}
+
+/// Callback for reporting the progress of long operations, in percent
+pub type ProgressCallback = Arc<dyn Fn(f32) + Send + Sync>;
+
+/// Progress of a long operation, reported at most once per percent
+#[derive(Clone, Default)]
+struct Progress
+{
+	callback: Option<ProgressCallback>,
+	last_percent: Option<usize>,
+}
+
+impl Progress
+{
+	fn is_enabled(&self) -> bool
+	{
+		self.callback.is_some()
+	}
+
+	/// Start reporting a new operation from zero
+	fn restart(&mut self)
+	{
+		self.last_percent = None;
+	}
+
+	/// Report the progress, if the percentage has changed since the last report
+	///
+	/// # Parameters
+	///
+	/// * `done`            - Amount of work done
+	/// * `total`           - Total amount of work
+	///
+	fn report(&mut self, done: usize, total: usize)
+	{
+		if let Some(callback) = &self.callback
+		{
+			let percent = std::cmp::min((done * 100).checked_div(total).unwrap_or(100), 100);
+			if self.last_percent != Some(percent)
+			{
+				self.last_percent = Some(percent);
+				callback(percent as f32);
+			}
+		}
+	}
+}
+
+/// The maze data structure
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Maze
 {
 	pub dimensions: Dimensions,
@@ -183,6 +533,45 @@ pub struct Maze
 	pub start: usize,
 	pub end: usize,
 	pub graph_created: bool,
+	#[serde(skip, default = "StdRng::from_entropy")]
+	rng: StdRng,
+	#[serde(skip)]
+	a_star: Option<AStarState>,
+	#[serde(skip)]
+	bfs: Option<BfsState>,
+	#[serde(skip)]
+	wall_follower: Option<WallFollowerState>,
+	#[serde(skip)]
+	dijkstra: Option<DijkstraState>,
+	#[serde(skip)]
+	generation: Option<GenerationState>,
+	#[serde(skip)]
+	progress: Progress,
+	#[serde(skip)]
+	cancel: Option<Arc<AtomicBool>>,
+	#[serde(skip, default = "default_max_cells")]
+	max_cells: usize,
+	#[serde(skip)]
+	solve_stats: Option<SolveStats>,
+}
+
+fn default_max_cells() -> usize
+{
+	MAZE_CELLS_MAX
+}
+
+/// Check that a maze of the given size has at most `max_cells` cells
+///
+/// Returns the number of cells, or AppError if there are too many
+/// or the number doesn't fit in a usize.
+fn check_cell_count(dimensions: Dimensions, max_cells: usize) -> Result<usize, AppError>
+{
+	match dimensions.width.checked_mul(dimensions.height)
+	{
+		Some(cells) if cells <= max_cells => Ok(cells),
+		_ => Err(AppError::new(&format!("Maze of {} cells is larger than the limit of {} cells",
+		                                dimensions, max_cells))),
+	}
 }
 
 impl std::fmt::Debug for Maze
@@ -193,849 +582,4789 @@ impl std::fmt::Debug for Maze
     }
 }
 
-impl Maze
+/// The maze is shown as rows of cell type glyphs, one line per row
+impl Display for Maze
 {
-	/// Create a new maze structure
-	pub fn new() -> Maze
-	{
-		let default_cell = MazeCell {
-			celltype: MazeCellType::Wall,
-			visited: false,
-			on_route: false,
-			nodes: [None; NUM_OF_DIRECTIONS],
-			text: String::new()};
-		let maze = Maze {
-			cells: vec![default_cell; MAZE_DIMENSION_DEFAULT * MAZE_DIMENSION_DEFAULT],
-			dimensions: Dimensions {
-				width: MAZE_DIMENSION_DEFAULT,
-				height: MAZE_DIMENSION_DEFAULT
-			},
-			start: 0,
-			end: 0,
-			graph_created: false,
-		};
-
-		return maze;
-	}
-
-	fn parse_header_line(&self, header: &String) -> Result<Dimensions, AppError>
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
 	{
-		let mut dimensions = Dimensions { width: 0, height: 0 };
-		let mut offset: usize = 0;
-		let radix = 10;
-
-		// parse "Maze" text
-		if header[offset..5] == *"Maze "
-		{
-			offset += 5;
-		}
-		else
+		for row in self.cells.chunks(self.dimensions.width)
 		{
-			return Err(AppError::new("Error reading maze file header"));
+			for cell in row
+			{
+				write!(f, "{}", cell.celltype)?;
+			}
+			writeln!(f)?;
 		}
 
-		// parse width
-		match header[offset..].chars().position(|c| c == ' ')
-		{
-			Some(n) => {
-				dimensions.width = usize::from_str_radix(&header[offset..offset+n], radix)?;
-				offset += n + 1;
-				debug!("Parsed width {}", dimensions.width);
-			},
-			None => return Err(AppError::new("Error parsing maze width from file header")),
-		}
+		Ok(())
+	}
+}
 
-		// parse height
-		dimensions.height = usize::from_str_radix(&header[offset..], radix)?;
-		debug!("Parsed height {}", dimensions.height);
+/// Mazes are equal when they have the same layout
+///
+/// Transient solving state, like visited cells and the route, is ignored.
+impl PartialEq for Maze
+{
+	fn eq(&self, other: &Self) -> bool
+	{
+		self.dimensions == other.dimensions &&
+			self.start == other.start &&
+			self.end == other.end &&
+			self.cells.len() == other.cells.len() &&
+			self.cells.iter().zip(other.cells.iter()).all(|(a, b)| a.celltype == b.celltype)
+	}
+}
 
-		Ok(dimensions)
+impl Maze
+{
+	/// Create a new maze structure
+	pub fn new() -> Maze
+	{
+		Maze::with_dimensions(Dimensions {
+			width: MAZE_DIMENSION_DEFAULT,
+			height: MAZE_DIMENSION_DEFAULT
+		})
 	}
 
-	/// Read a maze from a file
+	/// Create a new maze structure of the given size
 	///
-	/// Maze is read from a file to this instance of Maze, and
-	/// will overwrite any data already in this Maze.
+	/// All cells of the new maze are walls.
 	///
 	/// # Parameters
 	///
-	/// * `filename`        - Source filename for loading the maze
-	///
-	/// Returns AppError on failure.
+	/// * `dimensions`      - Odd width and height between MAZE_DIMENSION_MIN and MAZE_DIMENSION_MAX
 	///
-	pub fn read_from_file(&self, filename: &str) -> Result<(), AppError>
+	pub fn from_dimensions(dimensions: Dimensions) -> Result<Maze, AppError>
 	{
-		let path = Path::new(filename);
-		let display = path.display();
-		let file = match File::open(&path)
-		{
-			Err(e) => {
-				let error = format!("Couldn't open maze file {}: {}", display, e);
-				return Err(AppError::new(&error));
-			},
-			Ok(file) => file,
-		};
-		let mut lines = io::BufReader::new(file).lines();   // io::Lines<io::BufReader<File>>
-
-		println!("Maze read from file");
-		if let Some(Ok(header)) = lines.next()
-		{
-			self.parse_header_line(&header)?;
-		}
-
-		//TODO: parse the data instead of just printing it
-		for line in lines
+		for value in [dimensions.width, dimensions.height]
 		{
-			if let Ok(l) = line
+			if !(MAZE_DIMENSION_MIN..=MAZE_DIMENSION_MAX).contains(&value)
 			{
-				for c in l.chars()
-				{
-					//MazeCellType::from_str(&l[..1]);
-					//let foo = MazeCellType::from_str(c);
-					//TODO: from_str()
-					print!("{}", c);
-				}
-
-				println!("");
+				return Err(AppError::new(&format!("Maze dimensions must be between {} and {}",
+				                                  MAZE_DIMENSION_MIN, MAZE_DIMENSION_MAX)));
+			}
+			if value % 2 == 0
+			{
+				return Err(AppError::new("Maze dimensions must be odd"));
 			}
 		}
-		Ok(())
+		check_cell_count(dimensions, MAZE_CELLS_MAX)?;
+
+		Ok(Maze::with_dimensions(dimensions))
 	}
 
-	/// Save an already generated maze to a file
+	/// Create a maze from rows of cell glyphs
+	///
+	/// The glyphs are the same as in maze files, but there is no header.
+	/// The width is the length of the rows and the height is the number
+	/// of rows.
 	///
 	/// # Parameters
 	///
-	/// * `filename`        - Target filename for saving the maze
+	/// * `lines`           - Rows of the maze, all of the same length
 	///
-	/// Returns AppError on failure.
+	/// Returns AppError if the rows differ in length, a glyph is unknown
+	/// or the dimensions are invalid.
 	///
-	pub fn write_to_file(&self, filename: &str) -> Result<(), AppError>
+	pub fn from_char_grid(lines: &[String]) -> Result<Maze, AppError>
 	{
-		let path = Path::new(filename);
-		let display = path.display();
-
-		let mut file = match File::create(&path)
-		{
-			Err(e) => {
-				let error = format!("Couldn't create maze file {}: {}", display, e);
-				return Err(AppError::new(&error));
-			},
-			Ok(file) => file,
-		};
-
-		match writeln!(file, "Maze {} {}", self.dimensions.width, self.dimensions.height)
-		{
-			Err(e) => return Err(AppError::new(format!("Error writing maze: {}", e).as_str())),
-			Ok(_) => {}
-		}
-
-		for i in 0..self.dimensions.height
-		{
-			for j in 0..self.dimensions.width
-			{
-				match write!(file, "{}", self.cells[j + (i * self.dimensions.width)].celltype)
-				{
-					Err(e) => return Err(AppError::new(format!("Error writing maze: {}", e).as_str())),
-					Ok(_) => {}
-				}
-			}
-			match writeln!(file, "")
-			{
-				Err(e) => return Err(AppError::new(format!("Error writing maze: {}", e).as_str())),
-				Ok(_) => {}
-			}
-		}
+		let mut maze = Maze::from_dimensions(Maze::grid_dimensions(lines)?)?;
+		maze.parse_rows(lines)?;
 
-		return Ok(())
+		Ok(maze)
 	}
 
-	/// Reset a maze by clearing it content and resize it
-	/// to new dimensions if needed.
+	/// Create a maze for tests from a multi-line string
+	///
+	/// Walls are written as `#`, passages as spaces and the start and
+	/// the end as `S` and `E`. Empty lines are ignored. Unlike mazes
+	/// from `from_char_grid`, these may be smaller than MAZE_DIMENSION_MIN.
 	///
 	/// # Parameters
 	///
-	/// * `dimensions`      - New dimensions to set for the maze
+	/// * `text`            - Rows of the maze, one per line
 	///
-	pub fn reset(&mut self, dimensions: Dimensions)
+	#[cfg(test)]
+	pub fn from_ascii(text: &str) -> Result<Maze, AppError>
 	{
-		let new_size = dimensions.width * dimensions.height;
+		let lines: Vec<String> = text.lines()
+			.filter(|line| !line.is_empty())
+			.map(|line| line.replace('#', &MazeCellType::Wall.to_string()))
+			.collect();
 
-		self.dimensions = dimensions;
+		let mut maze = Maze::new();
+		maze.reset(Maze::grid_dimensions(&lines)?)?;
+		maze.parse_rows(&lines)?;
 
-		if self.cells.len() != new_size
+		Ok(maze)
+	}
+
+	/// Get the dimensions of rows of cell glyphs
+	///
+	/// Returns AppError if the rows differ in length.
+	fn grid_dimensions(lines: &[String]) -> Result<Dimensions, AppError>
+	{
+		let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+		if let Some(row) = lines.iter().position(|line| line.chars().count() != width)
 		{
-			let default_cell = MazeCell {
-				celltype: MazeCellType::Wall,
-				visited: false,
-				on_route: false,
-				nodes: [None; NUM_OF_DIRECTIONS],
-				text: String::new()};
-			self.cells.resize(new_size, default_cell);
+			let error = format!("Maze grid row {} doesn't match the width {}", row + 1, width);
+			return Err(AppError::new(&error));
 		}
 
-		for i in 0..new_size
+		Ok(Dimensions { width, height: lines.len() })
+	}
+
+	/// Parse rows of cell glyphs into a maze of the same size
+	fn parse_rows(&mut self, lines: &[String]) -> Result<(), AppError>
+	{
+		for (row, line) in lines.iter().enumerate()
 		{
-			self.cells[i].celltype = MazeCellType::Wall;
-			self.cells[i].visited = false;
-			self.cells[i].on_route = false;
+			self.parse_row(line, row)?;
 		}
 
-		debug!("Maze reset to new size: {} x {}, cells len: {}",
-			   self.dimensions.width,
-			   self.dimensions.height,
-			   self.cells.len());
+		Ok(())
 	}
 
-	/// Test if the given position in the Maze is diggable or not
-	/// to the given direction.
+	/// Create a copy of the maze with every cell enlarged to a square block
+	///
+	/// The blocks of the start and the end are passages, except for their
+	/// top left cells, which become the new start and end. The result is
+	/// meant for rendering and its dimensions may be even.
 	///
 	/// # Parameters
 	///
-	/// * `position`        - Position from the maze to test
-	/// * `direction`       - Direction of digging to test
+	/// * `factor`          - Width and height of the block replacing each cell
 	///
-	/// Returns a boolean value.
+	/// Returns AppError if the factor is zero or the scaled maze would be too large.
 	///
-	pub fn is_diggable(&self,
-	                   position: usize,
-	                   direction: Direction
-	) -> Result<bool, AppError>
+	pub fn scale(&self, factor: usize) -> Result<Maze, AppError>
 	{
-		let intermediate_position: usize = self.get_neighboring_position(position, direction)?;
-		let new_position: usize = self.get_neighboring_position(intermediate_position, direction)?;
-
-		// check the actual position is diggable (if it is, then also the intermediate is
-		if !self.is_wall_or_end_position(new_position)
+		if factor == 0
 		{
-			return Ok(false);
+			return Err(AppError::new("Maze can't be scaled by zero"));
 		}
-
-		debug!("Position: {}, new position: {}, direction: {}", position, new_position, direction);
-
-		// check all (other) positions around it (they must walls, or the end, all around)
-		let mut directions: Vec<Direction> = Direction::get_directions().iter().cloned().collect();
-		let opposite_direction = direction.get_opposite_direction();
-
-		if !Direction::remove_direction(&mut directions, opposite_direction)
+		let dimensions = match (self.dimensions.width.checked_mul(factor),
+		                        self.dimensions.height.checked_mul(factor))
 		{
-			return Err(AppError::new("Error while handling directions"));
-		}
+			(Some(width), Some(height)) => Dimensions { width, height },
+			_ => return Err(AppError::new("Scaled maze dimensions are too large")),
+		};
+		check_cell_count(dimensions, self.max_cells)?;
 
-		// check "sides" or "corners" of the new position and the test_position is also "diggable"
-		if self.are_sides_diggable(new_position, direction)
+		let mut scaled = Maze::with_dimensions(dimensions);
+		for (position, cell) in self.cells.iter().enumerate()
 		{
-			for test_direction in directions.iter()
+			let celltype = match cell.celltype
 			{
-				let test_position = self.get_neighboring_position(new_position, *test_direction)?;
-
-				if !self.is_wall_or_end_position(test_position)
+				MazeCellType::Start | MazeCellType::End => MazeCellType::Passage,
+				_ => cell.celltype.clone(),
+			};
+			let x = (position % self.dimensions.width) * factor;
+			let y = (position / self.dimensions.width) * factor;
+			for row in y..y + factor
+			{
+				for column in x..x + factor
 				{
-					debug!("Neighboring position {} is not a Wall or the End", test_position);
-					return Ok(false);
+					let target = &mut scaled.cells[column + row * dimensions.width];
+					target.celltype = celltype.clone();
+					target.weight = cell.weight;
 				}
 			}
-			return Ok(true);
 		}
 
-		return Ok(false);
+		let width = self.dimensions.width;
+		scaled.start = (self.start % width) * factor + (self.start / width) * factor * dimensions.width;
+		scaled.end = (self.end % width) * factor + (self.end / width) * factor * dimensions.width;
+		scaled.cells[scaled.start].celltype = MazeCellType::Start;
+		scaled.cells[scaled.end].celltype = MazeCellType::End;
+
+		Ok(scaled)
 	}
 
-	/// Dig a new passage to the maze.
+	/// Create a copy of the maze flipped over its diagonal
 	///
-	/// # Parameters
+	/// Rows become columns, so the start and the end move to the left
+	/// and the right edges.
+	pub fn transpose(&self) -> Maze
+	{
+		let dimensions = Dimensions {
+			width: self.dimensions.height,
+			height: self.dimensions.width
+		};
+		self.transformed(dimensions, |x, y| (y, x))
+	}
+
+	/// Create a copy of the maze mirrored from left to right
+	pub fn mirror_horizontal(&self) -> Maze
+	{
+		let width = self.dimensions.width;
+		self.transformed(self.dimensions, |x, y| (width - 1 - x, y))
+	}
+
+	/// Create a copy of the maze mirrored from top to bottom
+	pub fn mirror_vertical(&self) -> Maze
+	{
+		let height = self.dimensions.height;
+		self.transformed(self.dimensions, |x, y| (x, height - 1 - y))
+	}
+
+	/// Copy the cells and their weights to new coordinates
 	///
-	/// * `position`        - Starting position for the digging
-	/// * `direction`       - Direction of digging
+	/// Solving progress and the topology graph aren't copied.
 	///
-	/// Returns the new position where the digging ended.
-	/// That is two cells towards the given direction from the stating position.
+	/// # Parameters
 	///
-	pub fn dig_passage(&mut self,
-	                   position: usize,
-	                   direction: Direction
-	) -> Result<usize, AppError>
+	/// * `dimensions`      - Dimensions of the copy
+	/// * `map`             - Gives the coordinates in the copy for coordinates in this maze
+	///
+	fn transformed<F>(&self, dimensions: Dimensions, map: F) -> Maze
+		where F: Fn(usize, usize) -> (usize, usize)
 	{
-		let intermediate_position: usize = self.get_neighboring_position(position, direction)?;
-		let new_position: usize = self.get_neighboring_position(intermediate_position, direction)?;
-
-		if self.cells[intermediate_position].celltype != MazeCellType::Wall ||
-		   !self.is_wall_or_end_position(new_position)
-		{
-			let error = format!("Trying to dig something foul (positions: {}, {}) (types: {}, {})",
-			                    intermediate_position,
-			                    new_position,
-			                    self.cells[intermediate_position].celltype,
-			                    self.cells[new_position].celltype);
-			return Err(AppError::new(error.as_str()));
-		}
+		let mut copy = Maze::with_dimensions(dimensions);
+		let position_in_copy = |position: usize| {
+			let (x, y) = map(position % self.dimensions.width, position / self.dimensions.width);
+			x + y * dimensions.width
+		};
 
-		self.cells[intermediate_position].celltype = MazeCellType::Passage;
-		if self.cells[new_position].celltype != MazeCellType::End
+		for (position, cell) in self.cells.iter().enumerate()
 		{
-			self.cells[new_position].celltype = MazeCellType::Passage;
+			let target = &mut copy.cells[position_in_copy(position)];
+			target.celltype = cell.celltype.clone();
+			target.weight = cell.weight;
 		}
+		copy.start = position_in_copy(self.start);
+		copy.end = position_in_copy(self.end);
+		copy.max_cells = self.max_cells;
 
-		return Ok(new_position);
+		copy
 	}
 
-	/// Randomize the starting point for the maze generation.
-	///
-	/// Returns the randomized starting position.
-	pub fn randomize_start_position(&mut self) -> usize
+	fn with_dimensions(dimensions: Dimensions) -> Maze
 	{
-		let position = self.randomize_position_from_row(1);
-		self.cells[position].celltype = MazeCellType::Passage;
-		return position;
+		let default_cell = MazeCell {
+			celltype: MazeCellType::Wall,
+			visited: false,
+			on_route: false,
+			nodes: [None; NUM_OF_DIRECTIONS],
+			distance: None,
+			weight: DEFAULT_WEIGHT};
+		let maze = Maze {
+			cells: vec![default_cell; dimensions.width * dimensions.height],
+			dimensions,
+			start: 0,
+			end: 0,
+			graph_created: false,
+			rng: StdRng::from_entropy(),
+			a_star: None,
+			bfs: None,
+			wall_follower: None,
+			dijkstra: None,
+			generation: None,
+			progress: Progress::default(),
+			cancel: None,
+			max_cells: MAZE_CELLS_MAX,
+			solve_stats: None,
+		};
+
+		return maze;
 	}
 
-	/// Insert start and end cells to a maze
-	pub fn insert_start_and_end_positions(&mut self)
+	/// Get a cell of the maze by its coordinates
+	///
+	/// Returns None if the coordinates are outside of the maze.
+	pub fn get(&self, x: usize, y: usize) -> Option<&MazeCell>
 	{
-		let start_pos = self.randomize_position_from_row(0);
-		let end_pos = self.randomize_position_from_row(self.dimensions.height - 1);
-
-		self.cells[start_pos].celltype = MazeCellType::Start;
-		self.cells[end_pos].celltype = MazeCellType::End;
-
-		self.start = start_pos;
-		self.end = end_pos;
+		if x >= self.dimensions.width || y >= self.dimensions.height
+		{
+			return None;
+		}
+		self.cells.get(x + (y * self.dimensions.width))
 	}
 
-	fn is_wall_or_end_position(&self, position: usize) -> bool
+	/// Get a mutable cell of the maze by its coordinates
+	///
+	/// Returns None if the coordinates are outside of the maze.
+	pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut MazeCell>
 	{
-		if ![MazeCellType::Wall, MazeCellType::End].contains(&self.cells[position].celltype)
+		if x >= self.dimensions.width || y >= self.dimensions.height
 		{
-			return false;
+			return None;
 		}
-		return true;
+		self.cells.get_mut(x + (y * self.dimensions.width))
 	}
 
-	fn get_neighboring_position(&self,
-	                            position: usize,
-	                            direction: Direction
-	) -> Result<usize, AppError>
+	/// Set the weight of every passage in a rectangular area
+	///
+	/// The rectangle includes both corners and is clamped to the maze.
+	/// Walls are left untouched, the start and the end are weighted
+	/// like passages.
+	///
+	/// # Parameters
+	///
+	/// * `x0`              - Column of the first corner
+	/// * `y0`              - Row of the first corner
+	/// * `x1`              - Column of the opposite corner
+	/// * `y1`              - Row of the opposite corner
+	/// * `weight`          - Cost of moving to each cell in the area
+	///
+	pub fn set_weight_rect(&mut self, x0: usize, y0: usize, x1: usize, y1: usize, weight: u32)
 	{
-		let len = self.dimensions.width * self.dimensions.height;
+		if self.dimensions.width == 0 || self.dimensions.height == 0
+		{
+			return;
+		}
+
+		let left = std::cmp::min(x0, x1);
+		let top = std::cmp::min(y0, y1);
+		let right = std::cmp::min(std::cmp::max(x0, x1), self.dimensions.width - 1);
+		let bottom = std::cmp::min(std::cmp::max(y0, y1), self.dimensions.height - 1);
 
-		match direction
+		for y in top..=bottom
 		{
-			Direction::North => {
-				if position > self.dimensions.width
-				{
-					return Ok(position - self.dimensions.width);
-				}
-			},
-			Direction::East => {
-				if ((position + 1) < len) && ((position + 1) % self.dimensions.width != 0)
-				{
-					return Ok(position + 1);
-				}
-			},
-			Direction::West => {
-				if (position > 0) && (position % self.dimensions.width != 0)
-				{
-					return Ok(position - 1);
-				}
-			},
-			Direction::South => {
-				if (position + self.dimensions.width) < len
+			for x in left..=right
+			{
+				if let Some(cell) = self.get_mut(x, y)
 				{
-					return Ok(position + self.dimensions.width);
+					if cell.celltype != MazeCellType::Wall
+					{
+						cell.weight = weight;
+					}
 				}
-			},
-		};
-
-		return Err(AppError::new("Invalid maze position encountered"));
+			}
+		}
 	}
 
-	fn are_sides_diggable(&self, position: usize, direction: Direction) -> bool
+	fn parse_header_line(&self, header: &String) -> Result<Dimensions, AppError>
 	{
-		// check "sides" or "corners" of the test_position are also "diggable"
-		let mut sides: [usize; 2] = [0, 0];
-		let mut doable = false;
+		let mut dimensions = Dimensions { width: 0, height: 0 };
+		let mut offset: usize = 0;
+		let radix = 10;
 
-		if direction == Direction::North || direction == Direction::South
+		// parse "Maze" text
+		if header[offset..5] == *"Maze "
 		{
-			if let Ok(pos) = self.get_neighboring_position(position, Direction::East)
-			{
-				sides[0] = pos;
-			}
-			if let Ok(pos) = self.get_neighboring_position(position, Direction::West)
-			{
-				sides[1] = pos;
-			}
+			offset += 5;
 		}
 		else
 		{
-			if let Ok(pos) = self.get_neighboring_position(position, Direction::North)
-			{
-				sides[0] = pos;
-			}
-			if let Ok(pos) = self.get_neighboring_position(position, Direction::South)
-			{
-				sides[1] = pos;
-			}
+			return Err(AppError::new("Error reading maze file header"));
 		}
 
-		if self.is_wall_or_end_position(sides[0]) &&
-		   self.is_wall_or_end_position(sides[1])
+		// parse width
+		match header[offset..].chars().position(|c| c == ' ')
 		{
-			doable = true;
+			Some(n) => {
+				dimensions.width = usize::from_str_radix(&header[offset..offset+n], radix)?;
+				offset += n + 1;
+				debug!("Parsed width {}", dimensions.width);
+			},
+			None => return Err(AppError::new("Error parsing maze width from file header")),
 		}
 
-		return doable;
+		// parse height
+		dimensions.height = usize::from_str_radix(&header[offset..], radix)?;
+		debug!("Parsed height {}", dimensions.height);
+
+		Ok(dimensions)
 	}
 
-	fn randomize_position_from_row(&self, row: usize) -> usize
+	/// Parse one row of maze cell glyphs
+	///
+	/// # Parameters
+	///
+	/// * `line`            - Glyphs of the row, as many as the maze is wide
+	/// * `row`             - Index of the row
+	///
+	fn parse_row(&mut self, line: &str, row: usize) -> Result<(), AppError>
 	{
-		let mut rng = rand::thread_rng();
-		let mut position: usize = rng.gen_range(1..self.dimensions.width - 1);
-
-		if position % 2 == 0
+		for (x, c) in line.chars().enumerate()
 		{
-			position = position - 1;
+			let position = x + (row * self.dimensions.width);
+			let celltype = MazeCellType::from_str(&c.to_string())?;
+			if celltype == MazeCellType::Start
+			{
+				self.start = position;
+			}
+			else if celltype == MazeCellType::End
+			{
+				self.end = position;
+			}
+			self.cells[position].celltype = celltype;
 		}
 
-		position = position + (row * self.dimensions.width);
-
-		return position;
+		Ok(())
 	}
 
-	fn get_neighbours(&self, position: usize) -> Vec<usize>
+	/// Parse a weight line of a maze file
+	///
+	/// The weights belong to the last of the `rows` rows read so far.
+	fn parse_weight_line(&mut self, weights: &str, rows: usize) -> Result<(), AppError>
 	{
-		let directions: Vec<Direction> = Direction::get_directions().iter().cloned().collect();
-		let mut neighbours: Vec<usize> = Vec::new();
+		if rows == 0
+		{
+			return Err(AppError::new("Maze file has weights before the first row"));
+		}
 
-		for test_direction in directions
+		let row = rows - 1;
+		let weights = weights.split_whitespace()
+			.map(|w| w.parse::<u32>())
+			.collect::<Result<Vec<u32>, _>>()?;
+		if weights.len() != self.dimensions.width
+		{
+			let error = format!("Maze file weights of row {} don't match the width {}", row + 1, self.dimensions.width);
+			return Err(AppError::new(&error));
+		}
+
+		for (x, weight) in weights.into_iter().enumerate()
+		{
+			self.cells[x + (row * self.dimensions.width)].weight = weight;
+		}
+
+		Ok(())
+	}
+
+	/// Read a maze from a file
+	///
+	/// Maze is read from a file to this instance of Maze, and
+	/// will overwrite any data already in this Maze.
+	///
+	/// A row may be followed by a line starting with "W " and listing
+	/// the weights of its cells. Cells without weights have weight 1.
+	/// Files ending with `.maze.gz` are decompressed while reading.
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Source filename for loading the maze
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn read_from_file(&mut self, filename: &str) -> Result<(), AppError>
+	{
+		let path = Path::new(filename);
+		let display = path.display();
+		let file = match File::open(&path)
+		{
+			Err(e) => {
+				let error = format!("Couldn't open maze file {}: {}", display, e);
+				return Err(AppError::new(&error));
+			},
+			Ok(file) => file,
+		};
+		if Maze::is_compressed_file(filename)
+		{
+			self.read_maze(io::BufReader::new(GzDecoder::new(file)))?;
+		}
+		else
+		{
+			self.read_maze(io::BufReader::new(file))?;
+		}
+
+		info!("Maze read from file {}", display);
+		Ok(())
+	}
+
+	/// Read a maze in the format written by `write_maze`
+	///
+	/// # Parameters
+	///
+	/// * `reader`          - Source of the maze text
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn read_maze<R: BufRead>(&mut self, reader: R) -> Result<(), AppError>
+	{
+		let mut lines = reader.lines();
+
+		let dimensions = match lines.next()
+		{
+			Some(header) => self.parse_header_line(&header?)?,
+			None => return Err(AppError::new("Maze file is empty")),
+		};
+		self.reset(dimensions)?;
+
+		let mut rows = 0;
+		for line in lines
+		{
+			let l = line?;
+			if let Some(weights) = l.strip_prefix(WEIGHT_LINE_PREFIX)
+			{
+				self.parse_weight_line(weights, rows)?;
+				continue;
+			}
+			if rows >= dimensions.height
+			{
+				return Err(AppError::new("Maze file has more rows than its header specifies"));
+			}
+			if l.chars().count() != dimensions.width
+			{
+				let error = format!("Maze file row {} doesn't match the width {}", rows + 1, dimensions.width);
+				return Err(AppError::new(&error));
+			}
+
+			self.parse_row(&l, rows)?;
+			rows += 1;
+		}
+
+		if rows != dimensions.height
+		{
+			return Err(AppError::new("Maze file has fewer rows than its header specifies"));
+		}
+
+		Ok(())
+	}
+
+	/// Save an already generated maze to a file
+	///
+	/// The maze is first written to a temporary file next to the target,
+	/// which is then renamed over the target. An existing file is left
+	/// untouched if writing fails. Files ending with `.maze.gz` are
+	/// compressed with gzip.
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Target filename for saving the maze
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn write_to_file(&self, filename: &str) -> Result<(), AppError>
+	{
+		if !Maze::is_compressed_file(filename)
+		{
+			return Maze::write_atomically(filename, "maze file", |file| self.write_maze(file));
+		}
+
+		Maze::write_atomically(filename, "maze file", |file| {
+			let mut encoder = GzEncoder::new(file, Compression::default());
+			self.write_maze(&mut encoder)?;
+			encoder.finish()?;
+			Ok(())
+		})
+	}
+
+	/// Check if a maze file is compressed, by the ending of its name
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Name of the maze file
+	///
+	pub fn is_compressed_file(filename: &str) -> bool
+	{
+		filename.to_lowercase().ends_with(COMPRESSED_MAZE_SUFFIX)
+	}
+
+	/// Write the maze as text in the format used by maze files
+	///
+	/// # Parameters
+	///
+	/// * `writer`          - Target of the maze text
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn write_maze<W: Write>(&self, writer: &mut W) -> Result<(), AppError>
+	{
+		writeln!(writer, "Maze {} {}", self.dimensions.width, self.dimensions.height)?;
+
+		let text = self.to_string();
+		for (line, row) in text.lines().zip(self.cells.chunks(self.dimensions.width))
+		{
+			writeln!(writer, "{}", line)?;
+
+			// weights are written only for rows that have any
+			if row.iter().any(|cell| cell.weight != DEFAULT_WEIGHT)
+			{
+				let weights: Vec<String> = row.iter().map(|cell| cell.weight.to_string()).collect();
+				writeln!(writer, "{}{}", WEIGHT_LINE_PREFIX, weights.join(" "))?;
+			}
+		}
+
+		return Ok(())
+	}
+
+	/// Write a file through a temporary sibling file
+	///
+	/// The name of the temporary file is unique to this process and
+	/// call, so concurrent writers of the same file don't share it.
+	///
+	/// # Parameters
+	///
+	/// * `filename`        - Target filename
+	/// * `description`     - Kind of the file, used in error messages
+	/// * `write`           - Function writing the contents to the given file
+	///
+	/// The temporary file is removed if `write` fails.
+	///
+	pub(crate) fn write_atomically<F>(filename: &str, description: &str, write: F) -> Result<(), AppError>
+		where F: FnOnce(&mut File) -> Result<(), AppError>
+	{
+		let path = Path::new(filename);
+		let display = path.display();
+		let temp_filename = format!("{}.{}.{}.tmp", filename, std::process::id(),
+		                            TEMP_FILE_COUNTER.fetch_add(1, AtomicOrdering::Relaxed));
+		let temp_path = Path::new(&temp_filename);
+
+		let mut file = match File::create(temp_path)
+		{
+			Err(e) => {
+				let error = format!("Couldn't create {} {}: {}", description, display, e);
+				return Err(AppError::new(&error));
+			},
+			Ok(file) => file,
+		};
+
+		if let Err(e) = write(&mut file).and_then(|_| file.sync_all().map_err(AppError::from))
+		{
+			drop(file);
+			let _ = fs::remove_file(temp_path);
+			return Err(e);
+		}
+		drop(file);
+
+		if let Err(e) = fs::rename(temp_path, path)
+		{
+			let _ = fs::remove_file(temp_path);
+			let error = format!("Couldn't replace {} {}: {}", description, display, e);
+			return Err(AppError::new(&error));
+		}
+
+		Ok(())
+	}
+
+	/// Serialize the maze to JSON
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn to_json(&self) -> Result<String, AppError>
+	{
+		Ok(serde_json::to_string(self)?)
+	}
+
+	/// Deserialize a maze from JSON
+	///
+	/// Overwrites all data already in this Maze.
+	///
+	/// # Parameters
+	///
+	/// * `s`               - JSON produced by `to_json`
+	///
+	/// Returns AppError on failure, also if the dimensions are too large
+	/// or don't match the cells. This Maze is left untouched then.
+	///
+	#[allow(clippy::wrong_self_convention)]
+	pub fn from_json(&mut self, s: &str) -> Result<(), AppError>
+	{
+		let maze: Maze = serde_json::from_str(s)?;
+		let cells = check_cell_count(maze.dimensions, maze.max_cells)?;
+		if cells != maze.cells.len()
+		{
+			let error = format!("Maze of {} cells has data for {} cells", maze.dimensions, maze.cells.len());
+			return Err(AppError::new(&error));
+		}
+		if maze.start >= cells || maze.end >= cells
+		{
+			return Err(AppError::new("Maze start or end is outside the maze"));
+		}
+
+		*self = maze;
+		Ok(())
+	}
+
+	/// Reset a maze by clearing it content and resize it
+	/// to new dimensions if needed.
+	///
+	/// # Parameters
+	///
+	/// * `dimensions`      - New dimensions to set for the maze
+	///
+	/// Returns AppError if the maze would have more cells than allowed.
+	///
+	pub fn reset(&mut self, dimensions: Dimensions) -> Result<(), AppError>
+	{
+		let new_size = check_cell_count(dimensions, self.max_cells)?;
+
+		self.dimensions = dimensions;
+
+		if self.cells.len() != new_size
+		{
+			let default_cell = MazeCell {
+				celltype: MazeCellType::Wall,
+				visited: false,
+				on_route: false,
+				nodes: [None; NUM_OF_DIRECTIONS],
+				distance: None,
+				weight: DEFAULT_WEIGHT};
+			self.cells.resize(new_size, default_cell);
+		}
+		self.clear();
+
+		debug!("Maze reset to new size: {}, cells len: {}", self.dimensions, self.cells.len());
+		Ok(())
+	}
+
+	/// Clear the maze to walls, keeping its dimensions
+	///
+	/// The start and the end are reset to the first cell and
+	/// any topology graph and ongoing search are forgotten.
+	pub fn clear(&mut self)
+	{
+		for cell in self.cells.iter_mut()
+		{
+			cell.celltype = MazeCellType::Wall;
+			cell.visited = false;
+			cell.on_route = false;
+			cell.weight = DEFAULT_WEIGHT;
+			cell.nodes = [None; NUM_OF_DIRECTIONS];
+			cell.distance = None;
+		}
+		self.start = 0;
+		self.end = 0;
+		self.graph_created = false;
+		self.a_star = None;
+		self.bfs = None;
+		self.wall_follower = None;
+		self.dijkstra = None;
+		self.generation = None;
+	}
+
+	/// Set the largest number of cells allowed in this maze
+	///
+	/// # Parameters
+	///
+	/// * `max_cells`       - Cell count limit, MAZE_CELLS_MAX by default
+	///
+	pub fn set_max_cells(&mut self, max_cells: usize)
+	{
+		self.max_cells = max_cells;
+	}
+
+	/// Seed the random number generator used for generating mazes
+	///
+	/// Generating a maze of the same size after seeding with the same
+	/// seed produces an identical maze.
+	///
+	/// # Parameters
+	///
+	/// * `seed`            - Seed for the random number generator
+	///
+	pub fn set_seed(&mut self, seed: u64)
+	{
+		self.rng = StdRng::seed_from_u64(seed);
+	}
+
+	/// Set a function to call with the progress of generating and solving
+	///
+	/// The progress is reported in percent, at most once per percent.
+	///
+	/// # Parameters
+	///
+	/// * `callback`        - Function called with the progress, None to stop reporting
+	///
+	pub fn set_progress_callback(&mut self, callback: Option<ProgressCallback>)
+	{
+		self.progress.callback = callback;
+	}
+
+	/// Get the function called with the progress of generating and solving
+	pub fn progress_callback(&self) -> Option<ProgressCallback>
+	{
+		self.progress.callback.clone()
+	}
+
+	/// Set a flag which stops generating or solving when raised
+	///
+	/// A cancelled generation fails with an AppError and leaves the maze
+	/// partially generated. A cancelled search finishes without a route.
+	///
+	/// # Parameters
+	///
+	/// * `cancel`          - Shared cancellation flag, None to never cancel
+	///
+	pub fn set_cancel_flag(&mut self, cancel: Option<Arc<AtomicBool>>)
+	{
+		self.cancel = cancel;
+	}
+
+	/// Get the flag which stops generating or solving when raised
+	pub fn cancel_flag(&self) -> Option<Arc<AtomicBool>>
+	{
+		self.cancel.clone()
+	}
+
+	/// Check if the ongoing operation has been cancelled
+	pub fn is_cancelled(&self) -> bool
+	{
+		match &self.cancel
+		{
+			Some(cancel) => cancel.load(AtomicOrdering::SeqCst),
+			None => false,
+		}
+	}
+
+	fn check_cancelled(&self) -> Result<(), AppError>
+	{
+		if self.is_cancelled()
+		{
+			return Err(AppError::new("Cancelled"));
+		}
+		Ok(())
+	}
+
+	/// Number of rooms, cells on odd coordinates inside the outer walls
+	fn room_count(&self) -> usize
+	{
+		((self.dimensions.width - 1) / 2) * ((self.dimensions.height - 1) / 2)
+	}
+
+	/// Number of cells a search may visit
+	fn open_cell_count(&self) -> usize
+	{
+		self.cells.iter().filter(|cell| cell.celltype != MazeCellType::Wall).count()
+	}
+
+	/// Generate a new maze of the given size
+	///
+	/// The maze is carved with the given algorithm, after which the start
+	/// and end cells are inserted to the top and the bottom rows. This is
+	/// the same as calling `generate_step` until the maze is finished.
+	///
+	/// # Parameters
+	///
+	/// * `dimensions`      - The dimensions of a new maze to generate
+	/// * `method`          - The algorithm used for generating the maze
+	///
+	/// Returns AppError on failure.
+	///
+	pub fn generate(&mut self, dimensions: Dimensions, method: GenMethod) -> Result<(), AppError>
+	{
+		self.start_generation(dimensions, method)?;
+		while !self.generate_step()? {}
+
+		Ok(())
+	}
+
+	/// Start generating a new maze one passage at a time
+	///
+	/// The maze is cleared to walls and the algorithm is set up.
+	/// Call `generate_step` until it returns true to finish the maze.
+	/// The finished maze is the same as one generated with `generate`
+	/// from the same seed.
+	///
+	/// # Parameters
+	///
+	/// * `dimensions`      - The dimensions of a new maze to generate
+	/// * `method`          - The algorithm used for generating the maze
+	///
+	/// Returns AppError if the maze is too large for the algorithm.
+	///
+	pub fn start_generation(&mut self, dimensions: Dimensions, method: GenMethod) -> Result<(), AppError>
+	{
+		self.reset(dimensions)?;
+		self.progress.restart();
+
+		let state = match method
+		{
+			GenMethod::Backtracker => self.start_backtracker(),
+			GenMethod::Prim => self.start_prim(),
+			GenMethod::Kruskal => self.start_kruskal(),
+			GenMethod::AldousBroder => self.start_aldous_broder()?,
+		};
+		self.generation = Some(state);
+
+		Ok(())
+	}
+
+	/// Check if a maze is being generated one passage at a time
+	pub fn is_generating(&self) -> bool
+	{
+		self.generation.is_some()
+	}
+
+	/// Carve one passage of the maze being generated
+	///
+	/// When all rooms are connected, the start and the end are inserted
+	/// and the generation is finished.
+	///
+	/// Returns true if the maze is finished or no maze is being generated,
+	/// or AppError if the generation was cancelled. A cancelled maze is
+	/// left partially generated.
+	pub fn generate_step(&mut self) -> Result<bool, AppError>
+	{
+		let mut state = match self.generation.take()
+		{
+			Some(state) => state,
+			None => return Ok(true),
+		};
+
+		let carved = match &mut state
+		{
+			GenerationState::Backtracker { positions, dug } => self.dig_step(positions, dug)?,
+			GenerationState::Prim { frontier, added } => self.prim_step(frontier, added)?,
+			GenerationState::Kruskal { edges, next, sets, connected } => {
+				self.kruskal_step(edges, next, sets, connected)?
+			},
+			GenerationState::AldousBroder { position, unvisited } => {
+				self.aldous_broder_step(position, unvisited)?
+			},
+		};
+		if carved
+		{
+			self.generation = Some(state);
+			return Ok(false);
+		}
+
+		self.insert_start_and_end_positions();
+		self.open_edge_passage(self.start);
+		self.open_edge_passage(self.end);
+		let rooms = self.room_count();
+		self.progress.report(rooms, rooms);
+
+		if cfg!(debug_assertions)
+		{
+			self.verify()?;
+		}
+
+		Ok(true)
+	}
+
+	/// Start generating a maze with a simple recursive backtracking algorithm.
+	///
+	/// 1. Close all cells
+	/// 2. Choose starting cell and open it. This is the current cell
+	/// 3. Pick a cell adjacent to the current cell that hasn’t been visited and open it.
+	///    It becomes the current cell.
+	/// 4. Repeat 2 until no adjacent wall can be selected
+	/// 5. The previous cell becomes the current cell.
+	///    If this cell is the starting cell, then we are done. Else go to 2.
+	///
+	fn start_backtracker(&mut self) -> GenerationState
+	{
+		// generation could be started from any position, but we choose the start position
+		let position = self.randomize_start_position();
+		debug!("Start position: {}", position);
+
+		let mut positions: Vec<(usize, Direction)> = Vec::new();
+		Maze::push_new_position(&mut self.rng, &mut positions, position);
+
+		GenerationState::Backtracker { positions, dug: 1 }
+	}
+
+	/// Start generating a maze with the randomized Prim's algorithm.
+	///
+	/// Rooms (cells on odd coordinates) are added to the maze one at a time
+	/// through a randomly chosen wall between the maze and a room outside of it.
+	fn start_prim(&mut self) -> GenerationState
+	{
+		let first = self.randomize_start_position();
+		let frontier: Vec<(usize, Direction)> = Direction::get_directions().iter()
+			.map(|d| (first, *d))
+			.collect();
+
+		GenerationState::Prim { frontier, added: 1 }
+	}
+
+	/// Add one room to the maze with Prim's algorithm
+	///
+	/// Returns true if a room was added, false if the maze is finished.
+	fn prim_step(&mut self, frontier: &mut Vec<(usize, Direction)>, added: &mut usize) -> Result<bool, AppError>
+	{
+		let rooms = self.room_count();
+
+		while !frontier.is_empty()
+		{
+			self.check_cancelled()?;
+			let i = self.rng.gen_range(0..frontier.len());
+			let (position, direction) = frontier.swap_remove(i);
+
+			if let Some((wall, room)) = self.get_neighboring_room(position, direction)
+			{
+				if self.cells[room].celltype == MazeCellType::Wall
+				{
+					self.cells[wall].celltype = MazeCellType::Passage;
+					self.cells[room].celltype = MazeCellType::Passage;
+					*added += 1;
+					self.progress.report(*added, rooms);
+					for d in Direction::get_directions()
+					{
+						frontier.push((room, d));
+					}
+					return Ok(true);
+				}
+			}
+		}
+
+		Ok(false)
+	}
+
+	/// Generate a maze of the current dimensions with the recursive backtracker
+	///
+	/// Returns AppError if the generation was cancelled.
+	pub fn generate_backtracker(&mut self) -> Result<(), AppError>
+	{
+		self.generate(self.dimensions, GenMethod::Backtracker)
+	}
+
+	/// Generate a maze of the current dimensions with Prim's algorithm
+	///
+	/// Returns AppError if the generation was cancelled.
+	pub fn generate_prim(&mut self) -> Result<(), AppError>
+	{
+		self.generate(self.dimensions, GenMethod::Prim)
+	}
+
+	/// Generate a maze of the current dimensions with Kruskal's algorithm
+	///
+	/// Returns AppError if the generation was cancelled.
+	pub fn generate_kruskal(&mut self) -> Result<(), AppError>
+	{
+		self.generate(self.dimensions, GenMethod::Kruskal)
+	}
+
+	/// Generate a maze of the current dimensions with the Aldous-Broder algorithm.
+	///
+	/// Same as `generate` with `GenMethod::AldousBroder`, except the random
+	/// walk is driven by the given generator instead of the seed of this maze.
+	///
+	/// # Parameters
+	///
+	/// * `rng`             - Random number generator for the walk
+	///
+	/// Returns AppError if the maze is too large or the generation was cancelled.
+	///
+	pub fn generate_aldous_broder(&mut self, rng: &mut impl Rng) -> Result<(), AppError>
+	{
+		let walk_rng = StdRng::seed_from_u64(rng.gen());
+		let own_rng = std::mem::replace(&mut self.rng, walk_rng);
+		let result = self.generate(self.dimensions, GenMethod::AldousBroder);
+		self.rng = own_rng;
+		result
+	}
+
+	/// Start generating a maze with the Aldous-Broder algorithm.
+	///
+	/// A random walk moves between rooms and digs a passage whenever it
+	/// enters a room it has not visited before, until all rooms are visited.
+	/// Every possible maze is equally likely, but the walk takes on the order
+	/// of n log²(n) steps for n rooms, which makes it much slower than the
+	/// other algorithms on large mazes. The dimensions are limited to
+	/// ALDOUS_BRODER_DIMENSION_MAX, which takes a few seconds to generate
+	/// in a release build.
+	///
+	/// Returns AppError if the maze is too large.
+	///
+	fn start_aldous_broder(&mut self) -> Result<GenerationState, AppError>
+	{
+		let width = self.dimensions.width;
+		let height = self.dimensions.height;
+		if width > ALDOUS_BRODER_DIMENSION_MAX || height > ALDOUS_BRODER_DIMENSION_MAX
+		{
+			let error = format!("Aldous-Broder supports mazes up to {} x {}",
+			                    ALDOUS_BRODER_DIMENSION_MAX, ALDOUS_BRODER_DIMENSION_MAX);
+			return Err(AppError::new(&error));
+		}
+
+		let position = (self.rng.gen_range(0..(width - 1) / 2) * 2 + 1) +
+		               (self.rng.gen_range(0..(height - 1) / 2) * 2 + 1) * width;
+		self.cells[position].celltype = MazeCellType::Passage;
+
+		Ok(GenerationState::AldousBroder { position, unvisited: self.room_count() - 1 })
+	}
+
+	/// Walk randomly until a room not visited before is entered and dug
+	///
+	/// Returns true if a room was dug, false if all rooms are visited.
+	fn aldous_broder_step(&mut self, position: &mut usize, unvisited: &mut usize) -> Result<bool, AppError>
+	{
+		let directions = Direction::get_directions();
+		let rooms = self.room_count();
+
+		while *unvisited > 0
+		{
+			self.check_cancelled()?;
+			let direction = directions[self.rng.gen_range(0..NUM_OF_DIRECTIONS)];
+			if let Some((wall, room)) = self.get_neighboring_room(*position, direction)
+			{
+				let dug = self.cells[room].celltype == MazeCellType::Wall;
+				if dug
+				{
+					self.cells[wall].celltype = MazeCellType::Passage;
+					self.cells[room].celltype = MazeCellType::Passage;
+					*unvisited -= 1;
+					self.progress.report(rooms - *unvisited, rooms);
+				}
+				*position = room;
+				if dug
+				{
+					return Ok(true);
+				}
+			}
+		}
+
+		Ok(false)
+	}
+
+	/// Start generating a maze with the randomized Kruskal's algorithm.
+	///
+	/// Walls between rooms are removed in random order, if the rooms
+	/// on both sides of a wall are not yet connected to each other.
+	fn start_kruskal(&mut self) -> GenerationState
+	{
+		let sets: Vec<usize> = (0..self.cells.len()).collect();
+		let mut edges: Vec<(usize, usize, usize)> = Vec::new();
+
+		for position in 0..self.cells.len()
+		{
+			if !self.is_room(position)
+			{
+				continue;
+			}
+			for direction in [Direction::East, Direction::South]
+			{
+				if let Some((wall, room)) = self.get_neighboring_room(position, direction)
+				{
+					edges.push((position, wall, room));
+				}
+			}
+		}
+		edges.shuffle(&mut self.rng);
+
+		GenerationState::Kruskal { edges, next: 0, sets, connected: 1 }
+	}
+
+	/// Remove the next wall joining two rooms not yet connected to each other
+	///
+	/// Returns true if a wall was removed, false if all rooms are connected.
+	fn kruskal_step(&mut self, edges: &[(usize, usize, usize)], next: &mut usize,
+	                sets: &mut [usize], connected: &mut usize) -> Result<bool, AppError>
+	{
+		let rooms = self.room_count();
+
+		while let Some(&(a, wall, b)) = edges.get(*next)
+		{
+			self.check_cancelled()?;
+			*next += 1;
+			let set_a = Maze::find_set(sets, a);
+			let set_b = Maze::find_set(sets, b);
+			if set_a != set_b
+			{
+				sets[set_a] = set_b;
+				self.cells[a].celltype = MazeCellType::Passage;
+				self.cells[wall].celltype = MazeCellType::Passage;
+				self.cells[b].celltype = MazeCellType::Passage;
+				*connected += 1;
+				self.progress.report(*connected, rooms);
+				return Ok(true);
+			}
+		}
+
+		Ok(false)
+	}
+
+	fn find_set(sets: &mut [usize], position: usize) -> usize
+	{
+		let mut p = position;
+		while sets[p] != p
+		{
+			sets[p] = sets[sets[p]];
+			p = sets[p];
+		}
+		p
+	}
+
+	/// Test if the given position is a room, a cell on odd coordinates
+	/// inside the outer walls of the maze.
+	fn is_room(&self, position: usize) -> bool
+	{
+		let x = position % self.dimensions.width;
+		let y = position / self.dimensions.width;
+
+		x % 2 == 1 && y % 2 == 1 && x < self.dimensions.width - 1 && y < self.dimensions.height - 1
+	}
+
+	/// Get the wall and the room two cells away from the given position
+	/// to the given direction, if there is a room.
+	fn get_neighboring_room(&self, position: usize, direction: Direction) -> Option<(usize, usize)>
+	{
+		let wall = self.get_neighboring_position(position, direction).ok()?;
+		let room = self.get_neighboring_position(wall, direction).ok()?;
+
+		if self.is_room(room)
+		{
+			Some((wall, room))
+		}
+		else
+		{
+			None
+		}
+	}
+
+	/// Get the dead ends of the maze, passages with only one open neighbour
+	fn dead_ends(&self) -> Vec<usize>
+	{
+		(0..self.cells.len())
+			.filter(|&position| self.is_dead_end(position))
+			.collect()
+	}
+
+	fn is_dead_end(&self, position: usize) -> bool
+	{
+		self.cells[position].celltype == MazeCellType::Passage &&
+		self.get_neighbours(position).len() == 1
+	}
+
+	/// Count the dead ends of the maze, passages with only one open neighbour
+	pub fn count_dead_ends(&self) -> usize
+	{
+		self.dead_ends().len()
+	}
+
+	/// Count the passage cells of the maze, not including the start and the end
+	pub fn count_passages(&self) -> usize
+	{
+		self.cells.iter().filter(|cell| cell.celltype == MazeCellType::Passage).count()
+	}
+
+	/// Count the separate areas of passages in the maze
+	///
+	/// Passages, the start and the end that are connected to each other
+	/// form one region. A correctly generated maze has exactly one.
+	pub fn region_count(&self) -> usize
+	{
+		let mut reached = vec![false; self.cells.len()];
+		let mut queue: VecDeque<usize> = VecDeque::new();
+		let mut regions = 0;
+
+		for origin in 0..self.cells.len()
+		{
+			if reached[origin] || self.cells[origin].celltype == MazeCellType::Wall
+			{
+				continue;
+			}
+
+			regions += 1;
+			reached[origin] = true;
+			queue.push_back(origin);
+			while let Some(position) = queue.pop_front()
+			{
+				for p in self.get_neighbours(position)
+				{
+					if !reached[p]
+					{
+						reached[p] = true;
+						queue.push_back(p);
+					}
+				}
+			}
+		}
+
+		regions
+	}
+
+	/// Get a one line summary of the maze statistics
+	pub fn stats_summary(&self) -> String
+	{
+		format!("Passages: {}, dead ends: {}, regions: {}",
+		        self.count_passages(), self.count_dead_ends(), self.region_count())
+	}
+
+	/// Check the structure of a generated maze
+	///
+	/// A generated maze has exactly one start and one end, walls on
+	/// the whole border except for the start and the end, at least
+	/// a passage in every room and a route from the start to the end.
+	///
+	/// Returns AppError naming the first broken rule.
+	///
+	pub fn verify(&self) -> Result<(), AppError>
+	{
+		let width = self.dimensions.width;
+		let height = self.dimensions.height;
+		if width < 3 || height < 3 || self.cells.len() != width * height
+		{
+			let error = format!("Maze has {} cells, which doesn't match its size {}",
+			                    self.cells.len(), self.dimensions);
+			return Err(AppError::new(&error));
+		}
+
+		for (celltype, position, name) in [(MazeCellType::Start, self.start, "start"),
+		                                   (MazeCellType::End, self.end, "end")]
+		{
+			let count = self.cells.iter().filter(|cell| cell.celltype == celltype).count();
+			if count != 1 || self.cells.get(position).map(|cell| &cell.celltype) != Some(&celltype)
+			{
+				let error = format!("Maze should have one {} at position {}, found {}",
+				                    name, position, count);
+				return Err(AppError::new(&error));
+			}
+		}
+
+		let on_border = |p: usize| {
+			let x = p % width;
+			p < width || p >= width * (height - 1) || x == 0 || x == width - 1
+		};
+		if let Some(p) = (0..self.cells.len()).find(|&p| on_border(p) &&
+		                                            self.cells[p].celltype == MazeCellType::Passage)
+		{
+			let error = format!("Border cell at ({}, {}) isn't a wall", p % width, p / width);
+			return Err(AppError::new(&error));
+		}
+
+		let passages = self.count_passages();
+		let most = (width - 2) * (height - 2);
+		if passages < self.room_count() || passages > most
+		{
+			let error = format!("Maze has {} passages, expected from {} to {}",
+			                    passages, self.room_count(), most);
+			return Err(AppError::new(&error));
+		}
+
+		if !self.is_solvable()
+		{
+			return Err(AppError::new("There is no route from the start to the end"));
+		}
+
+		Ok(())
+	}
+
+	/// Remove dead ends from the maze to create loops
+	///
+	/// Each dead end is opened, with the given probability, by digging
+	/// through a wall to a neighbouring room.
+	///
+	/// # Parameters
+	///
+	/// * `ratio`           - Probability of removing a dead end, from 0.0 to 1.0
+	/// * `rng`             - Random number generator used for the choices
+	///
+	pub fn braid(&mut self, ratio: f32, rng: &mut impl Rng)
+	{
+		for position in self.dead_ends()
+		{
+			// opening an earlier dead end may have already opened this one
+			if !self.is_dead_end(position) || !rng.gen_bool(ratio.clamp(0.0, 1.0) as f64)
+			{
+				continue;
+			}
+
+			let walls: Vec<usize> = Direction::get_directions().iter()
+				.filter_map(|&direction| self.get_neighboring_room(position, direction))
+				.filter(|&(wall, _room)| self.cells[wall].celltype == MazeCellType::Wall)
+				.map(|(wall, _room)| wall)
+				.collect();
+
+			if let Some(&wall) = walls.choose(rng)
+			{
+				self.cells[wall].celltype = MazeCellType::Passage;
+			}
+		}
+	}
+
+	/// Remove dead ends using the random number generator of the maze
+	///
+	/// Keeps braiding a seeded maze reproducible.
+	///
+	/// # Parameters
+	///
+	/// * `ratio`           - Probability of removing a dead end, from 0.0 to 1.0
+	///
+	pub fn braid_randomly(&mut self, ratio: f32)
+	{
+		let mut rng = std::mem::replace(&mut self.rng, StdRng::seed_from_u64(0));
+		self.braid(ratio, &mut rng);
+		self.rng = rng;
+	}
+
+	/// Dig one passage of a maze generated with the backtracking algorithm
+	///
+	/// Steps back along the dug passages until a new passage can be dug.
+	///
+	/// # Parameters
+	///
+	/// * `positions`       - Cells and directions left to dig to, the latest last
+	/// * `dug`             - Number of rooms dug so far
+	///
+	/// Returns true if a passage was dug, false if the maze is finished.
+	///
+	fn dig_step(&mut self, positions: &mut Vec<(usize, Direction)>, dug: &mut usize) -> Result<bool, AppError>
+	{
+		let rooms = self.room_count();
+
+		while let Some((position, direction)) = positions.pop()
+		{
+			self.check_cancelled()?;
+			debug!("Moving to position {}", position);
+
+			debug!("Checking if digging possible at position {}", position);
+			match self.is_diggable(position, direction)
+			{
+				Ok(result) => {
+					if result
+					{
+						debug!("Digging new passage towards {}", direction);
+						let new_position = self.dig_passage(position, direction)?;
+						*dug += 1;
+						self.progress.report(*dug, rooms);
+						Maze::push_new_position(&mut self.rng, positions, new_position);
+						return Ok(true);
+					}
+					else
+					{
+						debug!("Can't dig to {}", direction);
+					}
+				},
+				Err(e) => {
+					debug!("Can't dig to {}, error: {}", direction, e);
+				}
+			}
+			debug!("Stepping back from {}", position);
+		}
+		Ok(false)
+	}
+
+	fn push_new_position(rng: &mut StdRng, positions: &mut Vec<(usize, Direction)>, position: usize)
+	{
+		let mut directions = Direction::get_directions();
+		directions.shuffle(rng);
+
+		for direction in directions.iter()
+		{
+			positions.push((position, *direction));
+		}
+	}
+
+	/// Test if the given position in the Maze is diggable or not
+	/// to the given direction.
+	///
+	/// # Parameters
+	///
+	/// * `position`        - Position from the maze to test
+	/// * `direction`       - Direction of digging to test
+	///
+	/// Returns a boolean value.
+	///
+	pub fn is_diggable(&self,
+	                   position: usize,
+	                   direction: Direction
+	) -> Result<bool, AppError>
+	{
+		let intermediate_position: usize = self.get_neighboring_position(position, direction)?;
+		let new_position: usize = self.get_neighboring_position(intermediate_position, direction)?;
+
+		// check the actual position is diggable (if it is, then also the intermediate is
+		if !self.is_wall_or_end_position(new_position)
+		{
+			return Ok(false);
+		}
+
+		debug!("Position: {}, new position: {}, direction: {}", position, new_position, direction);
+
+		// check all (other) positions around it (they must walls, or the end, all around)
+		let mut directions: Vec<Direction> = Direction::get_directions().iter().cloned().collect();
+		let opposite_direction = direction.get_opposite_direction();
+
+		if !Direction::remove_direction(&mut directions, opposite_direction)
+		{
+			return Err(AppError::new("Error while handling directions"));
+		}
+
+		// check "sides" or "corners" of the new position and the test_position is also "diggable"
+		if self.are_sides_diggable(new_position, direction)
+		{
+			for test_direction in directions.iter()
+			{
+				let test_position = self.get_neighboring_position(new_position, *test_direction)?;
+
+				if !self.is_wall_or_end_position(test_position)
+				{
+					debug!("Neighboring position {} is not a Wall or the End", test_position);
+					return Ok(false);
+				}
+			}
+			return Ok(true);
+		}
+
+		return Ok(false);
+	}
+
+	/// Dig a new passage to the maze.
+	///
+	/// # Parameters
+	///
+	/// * `position`        - Starting position for the digging
+	/// * `direction`       - Direction of digging
+	///
+	/// Returns the new position where the digging ended.
+	/// That is two cells towards the given direction from the stating position.
+	///
+	pub fn dig_passage(&mut self,
+	                   position: usize,
+	                   direction: Direction
+	) -> Result<usize, AppError>
+	{
+		let intermediate_position: usize = self.get_neighboring_position(position, direction)?;
+		let new_position: usize = self.get_neighboring_position(intermediate_position, direction)?;
+
+		if self.cells[intermediate_position].celltype != MazeCellType::Wall ||
+		   !self.is_wall_or_end_position(new_position)
+		{
+			let error = format!("Trying to dig something foul (positions: {}, {}) (types: {}, {})",
+			                    intermediate_position,
+			                    new_position,
+			                    self.cells[intermediate_position].celltype,
+			                    self.cells[new_position].celltype);
+			return Err(AppError::new(error.as_str()));
+		}
+
+		self.cells[intermediate_position].celltype = MazeCellType::Passage;
+		if self.cells[new_position].celltype != MazeCellType::End
+		{
+			self.cells[new_position].celltype = MazeCellType::Passage;
+		}
+
+		return Ok(new_position);
+	}
+
+	/// Randomize the starting point for the maze generation.
+	///
+	/// Returns the randomized starting position.
+	pub fn randomize_start_position(&mut self) -> usize
+	{
+		let position = self.randomize_position_from_row(1);
+		self.cells[position].celltype = MazeCellType::Passage;
+		return position;
+	}
+
+	/// Insert start and end cells to a maze
+	///
+	/// The start is placed on the first row and the end on the last row,
+	/// never in the same column, so the route isn't just a straight line.
+	pub fn insert_start_and_end_positions(&mut self)
+	{
+		let width = self.dimensions.width;
+		let start_pos = self.randomize_position_from_row(0);
+		let mut end_pos = self.randomize_position_from_row(self.dimensions.height - 1);
+		while end_pos % width == start_pos % width
+		{
+			end_pos = self.randomize_position_from_row(self.dimensions.height - 1);
+		}
+
+		self.cells[start_pos].celltype = MazeCellType::Start;
+		self.cells[end_pos].celltype = MazeCellType::End;
+
+		self.start = start_pos;
+		self.end = end_pos;
+	}
+
+	/// Make sure a cell on the border of the maze connects to its inside
+	///
+	/// If the cell has no neighbors which are passages, the neighboring
+	/// cell towards the inside of the maze is opened as a passage.
+	/// Cells not on the border and corner cells are left untouched.
+	///
+	/// # Parameters
+	///
+	/// * `position`        - Position of the border cell, usually the start or the end
+	///
+	pub fn open_edge_passage(&mut self, position: usize)
+	{
+		let width = self.dimensions.width;
+		let height = self.dimensions.height;
+		if position >= width * height || !self.get_neighbours(position).is_empty()
+		{
+			return;
+		}
+
+		let x = position % width;
+		let y = position / width;
+		let inward = if (x == 0 || x == width - 1) && (y == 0 || y == height - 1)
+		{
+			None
+		}
+		else if y == 0
+		{
+			Some(Direction::South)
+		}
+		else if y == height - 1
+		{
+			Some(Direction::North)
+		}
+		else if x == 0
+		{
+			Some(Direction::East)
+		}
+		else if x == width - 1
+		{
+			Some(Direction::West)
+		}
+		else
+		{
+			None
+		};
+
+		if let Some(direction) = inward
+		{
+			if let Ok(inside) = self.get_neighboring_position(position, direction)
+			{
+				debug!("Opening a passage next to the border cell {}", position);
+				self.cells[inside].celltype = MazeCellType::Passage;
+			}
+		}
+	}
+
+	/// Move the start and the end of the maze to new positions
+	///
+	/// The old start and end cells become passages and any previous
+	/// solving progress is cleared.
+	///
+	/// # Parameters
+	///
+	/// * `start`           - Position of the new start cell
+	/// * `end`             - Position of the new end cell
+	///
+	/// Returns AppError if either position is outside the maze or a wall.
+	///
+	pub fn set_endpoints(&mut self, start: usize, end: usize) -> Result<(), AppError>
+	{
+		for position in [start, end]
+		{
+			self.check_endpoint_position(position)?;
+		}
+
+		self.demote_endpoint(self.start, MazeCellType::Start);
+		self.demote_endpoint(self.end, MazeCellType::End);
+		self.cells[start].celltype = MazeCellType::Start;
+		self.cells[end].celltype = MazeCellType::End;
+		self.start = start;
+		self.end = end;
+
+		self.clear_solving_state();
+
+		Ok(())
+	}
+
+	/// Move the start of the maze to a new position
+	///
+	/// The old start becomes a passage and any previous solving progress
+	/// is cleared.
+	///
+	/// # Parameters
+	///
+	/// * `x`               - X coordinate of the new start cell
+	/// * `y`               - Y coordinate of the new start cell
+	///
+	/// Returns AppError if the position is outside the maze, a wall or the end.
+	///
+	pub fn set_start(&mut self, x: usize, y: usize) -> Result<(), AppError>
+	{
+		let position = self.endpoint_position(x, y, MazeCellType::End)?;
+		self.demote_endpoint(self.start, MazeCellType::Start);
+		self.cells[position].celltype = MazeCellType::Start;
+		self.start = position;
+
+		self.clear_solving_state();
+
+		Ok(())
+	}
+
+	/// Move the end of the maze to a new position
+	///
+	/// The old end becomes a passage and any previous solving progress
+	/// is cleared.
+	///
+	/// # Parameters
+	///
+	/// * `x`               - X coordinate of the new end cell
+	/// * `y`               - Y coordinate of the new end cell
+	///
+	/// Returns AppError if the position is outside the maze, a wall or the start.
+	///
+	pub fn set_end(&mut self, x: usize, y: usize) -> Result<(), AppError>
+	{
+		let position = self.endpoint_position(x, y, MazeCellType::Start)?;
+		self.demote_endpoint(self.end, MazeCellType::End);
+		self.cells[position].celltype = MazeCellType::End;
+		self.end = position;
+
+		self.clear_solving_state();
+
+		Ok(())
+	}
+
+	/// Check that an endpoint can be placed at the given position
+	fn check_endpoint_position(&self, position: usize) -> Result<(), AppError>
+	{
+		match self.cells.get(position)
+		{
+			None => Err(AppError::new(&format!("Position {} is outside the maze", position))),
+			Some(cell) if cell.celltype == MazeCellType::Wall => {
+				Err(AppError::new(&format!("Position {} is a wall", position)))
+			},
+			Some(_) => Ok(()),
+		}
+	}
+
+	/// Get the position of a new endpoint at the given coordinates
+	///
+	/// The position can't be a wall or the other endpoint of type `other`.
+	fn endpoint_position(&self, x: usize, y: usize, other: MazeCellType) -> Result<usize, AppError>
+	{
+		if x >= self.dimensions.width || y >= self.dimensions.height
+		{
+			return Err(AppError::new(&format!("Position ({}, {}) is outside the maze", x, y)));
+		}
+
+		let position = x + y * self.dimensions.width;
+		self.check_endpoint_position(position)?;
+		if self.cells[position].celltype == other
+		{
+			return Err(AppError::new(&format!("Position ({}, {}) is already the {}", x, y,
+			                                  if other == MazeCellType::Start { "start" } else { "end" })));
+		}
+
+		Ok(position)
+	}
+
+	/// Turn an old endpoint of the given type back into a passage
+	fn demote_endpoint(&mut self, position: usize, celltype: MazeCellType)
+	{
+		if let Some(cell) = self.cells.get_mut(position)
+		{
+			if cell.celltype == celltype
+			{
+				cell.celltype = MazeCellType::Passage;
+			}
+		}
+	}
+
+	/// Clear the visited cells, the route and the distances
+	///
+	/// Any ongoing search is forgotten, but the maze itself and its
+	/// topology graph are kept, so it can be solved again.
+	pub fn clear_solution(&mut self)
+	{
+		self.clear_route_marks();
+		for cell in self.passages_iter_mut()
+		{
+			cell.distance = None;
+		}
+		self.clear_searches();
+	}
+
+	/// Forget any ongoing search
+	fn clear_searches(&mut self)
+	{
+		self.a_star = None;
+		self.bfs = None;
+		self.wall_follower = None;
+		self.dijkstra = None;
+	}
+
+	/// Clear the visited cells and the route left by an earlier search
+	fn clear_route_marks(&mut self)
+	{
+		for cell in self.cells.iter_mut()
+		{
+			cell.visited = false;
+			cell.on_route = false;
+		}
+		self.solve_stats = None;
+	}
+
+	/// Get the measurements of the last search done by `solve`
+	///
+	/// Returns None if the maze hasn't been solved since the route
+	/// was last cleared, or the search timed out.
+	pub fn solve_stats(&self) -> Option<SolveStats>
+	{
+		self.solve_stats
+	}
+
+	/// Forget everything found by the solvers, including the topology graph
+	fn clear_solving_state(&mut self)
+	{
+		self.clear_solution();
+		for cell in self.cells.iter_mut()
+		{
+			cell.nodes = [None; NUM_OF_DIRECTIONS];
+		}
+		self.graph_created = false;
+	}
+
+	fn is_wall_or_end_position(&self, position: usize) -> bool
+	{
+		if ![MazeCellType::Wall, MazeCellType::End].contains(&self.cells[position].celltype)
+		{
+			return false;
+		}
+		return true;
+	}
+
+	fn get_neighboring_position(&self,
+	                            position: usize,
+	                            direction: Direction
+	) -> Result<usize, AppError>
+	{
+		let width = self.dimensions.width;
+		let height = self.dimensions.height;
+
+		if position < width * height
+		{
+			let (row_offset, column_offset) = direction.to_offset();
+			let row = (position / width) as isize + row_offset;
+			let column = (position % width) as isize + column_offset;
+
+			if row >= 0 && row < height as isize && column >= 0 && column < width as isize
+			{
+				return Ok(row as usize * width + column as usize);
+			}
+		}
+
+		return Err(AppError::new("Invalid maze position encountered"));
+	}
+
+	fn are_sides_diggable(&self, position: usize, direction: Direction) -> bool
+	{
+		// check "sides" or "corners" of the test_position are also "diggable"
+		let mut sides: [usize; 2] = [0, 0];
+		let mut doable = false;
+
+		if direction == Direction::North || direction == Direction::South
+		{
+			if let Ok(pos) = self.get_neighboring_position(position, Direction::East)
+			{
+				sides[0] = pos;
+			}
+			if let Ok(pos) = self.get_neighboring_position(position, Direction::West)
+			{
+				sides[1] = pos;
+			}
+		}
+		else
+		{
+			if let Ok(pos) = self.get_neighboring_position(position, Direction::North)
+			{
+				sides[0] = pos;
+			}
+			if let Ok(pos) = self.get_neighboring_position(position, Direction::South)
+			{
+				sides[1] = pos;
+			}
+		}
+
+		if self.is_wall_or_end_position(sides[0]) &&
+		   self.is_wall_or_end_position(sides[1])
+		{
+			doable = true;
+		}
+
+		return doable;
+	}
+
+	fn randomize_position_from_row(&mut self, row: usize) -> usize
+	{
+		let mut position: usize = self.rng.gen_range(1..self.dimensions.width - 1);
+
+		if position % 2 == 0
+		{
+			position = position - 1;
+		}
+
+		position = position + (row * self.dimensions.width);
+
+		return position;
+	}
+
+	fn get_neighbours(&self, position: usize) -> Vec<usize>
+	{
+		self.neighbors_with_directions(position)
+			.into_iter()
+			.map(|(_, pos)| pos)
+			.collect()
+	}
+
+	/// Get the neighboring cells which are not walls
+	///
+	/// # Parameters
+	///
+	/// * `position`        - Position of the cell
+	///
+	/// Returns the direction taken from the cell to each neighbor
+	/// paired with the position of the neighbor.
+	///
+	pub fn neighbors_with_directions(&self, position: usize) -> Vec<(Direction, usize)>
+	{
+		let mut neighbours: Vec<(Direction, usize)> = Vec::new();
+
+		for test_direction in Direction::get_directions()
+		{
+			if let Ok(pos) = self.get_neighboring_position(position, test_direction)
+			{
+				if self.cells[pos].celltype != MazeCellType::Wall
+				{
+					neighbours.push((test_direction, pos));
+				}
+			}
+		}
+
+		neighbours
+	}
+
+	/// Get the passage connectivity of the maze as an adjacency list.
+	///
+	/// Returns, for each cell index, the indices of the neighboring
+	/// cells which are not walls. The list is empty for walls.
+	pub fn passage_adjacency(&self) -> Vec<Vec<usize>>
+	{
+		(0..self.cells.len()).map(|position| {
+			if self.cells[position].celltype == MazeCellType::Wall
+			{
+				Vec::new()
+			}
+			else
+			{
+				self.get_neighbours(position)
+			}
+		}).collect()
+	}
+
+	/// Convert a cell index to its column and row
+	///
+	/// # Parameters
+	///
+	/// * `position`        - Index of the cell
+	///
+	/// Returns the coordinates as `(x, y)`.
+	fn convert_position_to_coordinates(&self, position: usize) -> (usize, usize)
+	{
+		(position % self.dimensions.width, position / self.dimensions.width)
+	}
+
+	/// Manhattan distance between two cells, the heuristic of A*
+	///
+	/// # Parameters
+	///
+	/// * `a`               - Index of the first cell
+	/// * `b`               - Index of the second cell
+	///
+	fn manhattan_distance(&self, a: usize, b: usize) -> usize
+	{
+		let (ax, ay) = self.convert_position_to_coordinates(a);
+		let (bx, by) = self.convert_position_to_coordinates(b);
+
+		let h = i64::abs(ax as i64 - bx as i64) as usize;
+		let v = i64::abs(ay as i64 - by as i64) as usize;
+
+		return h + v;
+	}
+
+	/// Solve the maze from the beginning with the given method.
+	///
+	/// Any ongoing search is restarted. The searches are run one step
+	/// at a time, so they can be stopped on pathological mazes. Every
+	/// cell walked while building the topology graph counts as a step,
+	/// but reducing the graph always runs to completion.
+	///
+	/// # Parameters
+	///
+	/// * `method`          - Method to use to solve the maze
+	/// * `max_steps`       - Maximum number of search steps, None for no limit
+	///
+	/// Returns whether a route was found. The route and the visited
+	/// cells are cleared if the search timed out. Measurements of
+	/// the search are available from `solve_stats` afterwards.
+	pub fn solve(&mut self, method: SolveMethod, max_steps: Option<usize>) -> SolveResult
+	{
+		let begin = Instant::now();
+		self.clear_route_marks();
+		self.clear_searches();
+
+		let mut total = 0;
+		if self.progress.is_enabled()
+		{
+			total = self.open_cell_count();
+		}
+		self.progress.restart();
+
+		let mut steps = 0;
+		loop
+		{
+			if max_steps.is_some_and(|max| steps >= max)
+			{
+				info!("Solving with {} stopped after {} steps", method, steps);
+				self.clear_route_marks();
+				self.clear_searches();
+				return SolveResult::TimedOut;
+			}
+			steps += 1;
+			if total > 0
+			{
+				self.progress.report(std::cmp::min(steps, total), total);
+			}
+
+			let finished = match method
+			{
+				SolveMethod::GraphOnly | SolveMethod::GraphElimination => {
+					if !self.graph_created
+					{
+						steps += self.build_topology_graph(max_steps.map(|max| max.saturating_sub(steps)));
+						if !self.graph_created
+						{
+							continue;
+						}
+					}
+					if method == SolveMethod::GraphElimination
+					{
+						self.run_graph_elimination(false);
+					}
+					self.mark_graph_route();
+					true
+				},
+				SolveMethod::AStar => self.run_a_star(true),
+				SolveMethod::Bfs => self.run_bfs(true),
+				SolveMethod::Dijkstra => self.run_dijkstra(true),
+				SolveMethod::WallFollower => self.run_wall_follower(true),
+			};
+			if finished
+			{
+				break;
+			}
+		}
+
+		self.solve_stats = Some(SolveStats {
+			steps,
+			path_len: self.path_length().unwrap_or(0),
+			cells_visited: self.cells.iter().filter(|cell| cell.visited).count(),
+			elapsed: begin.elapsed(),
+		});
+
+		if self.cells[self.end].on_route
+		{
+			SolveResult::Solved
+		}
+		else
+		{
+			SolveResult::NoPath
+		}
+	}
+
+	/// Find the cheapest route from the start to the end with the A* algorithm.
+	///
+	/// The cheapest known cost of each cell is kept and the search ends
+	/// only when the end is taken from the open list, so the route is
+	/// the cheapest one also in weighted and braided mazes. The distance
+	/// to the end is estimated with the cost of the cheapest cell.
+	///
+	/// # Parameters
+	///
+	/// * `step`            - Process only one node from the open list per call
+	///
+	/// Returns true when the search is finished and the route, if any, is marked.
+	pub fn run_a_star(&mut self, step: bool) -> bool
+	{
+		if self.mark_trivial_route()
+		{
+			return true;
+		}
+
+		// continue an ongoing search or start a new one
+		let mut state = match self.a_star.take()
+		{
+			Some(state) => state,
+			None => {
+				self.clear_route_marks();
+				let min_cost = self.cells.iter()
+					.filter(|cell| cell.celltype != MazeCellType::Wall)
+					.map(|cell| cell.weight as usize)
+					.min()
+					.unwrap_or(0);
+				let mut state = AStarState::new(self.cells.len(), min_cost);
+				state.costs[self.start] = Some(0);
+				let f = self.manhattan_distance(self.start, self.end) * min_cost;
+				state.open_list.push(Reverse(AStarItem { position: self.start, f, g: 0 }));
+				state
+			}
+		};
+
+		let mut total = 0;
+		if !step && self.progress.is_enabled()
+		{
+			total = self.open_cell_count();
+		}
+		let mut searched = 0;
+		self.progress.restart();
+
+		while let Some(Reverse(item)) = state.open_list.pop()
+		{
+			if self.is_cancelled()
+			{
+				info!("Search cancelled");
+				return true;
+			}
+			// skip entries made obsolete by a cheaper route found later
+			if state.costs[item.position] != Some(item.g)
+			{
+				continue;
+			}
+			self.cells[item.position].visited = true;
+			searched += 1;
+			if total > 0
+			{
+				self.progress.report(searched, total);
+			}
+
+			// the end is reached by the cheapest route only when it is taken from the open list
+			if item.position == self.end
+			{
+				let mut current = Some(item.position);
+				while let Some(p) = current
+				{
+					self.cells[p].on_route = true;
+					current = state.parents[p];
+				}
+				return true;
+			}
+
+			for p in self.get_neighbours(item.position)
+			{
+				let g = item.g + self.step_cost(p);
+				if state.costs[p].is_none_or(|c| g < c)
+				{
+					state.costs[p] = Some(g);
+					state.parents[p] = Some(item.position);
+					let f = g + self.manhattan_distance(p, self.end) * state.min_cost;
+					state.open_list.push(Reverse(AStarItem { position: p, f, g }));
+				}
+			}
+
+			if step
+			{
+				self.a_star = Some(state);
+				return false;
+			}
+		}
+
+		info!("A* found no route");
+		true
+	}
+
+	/// Flood fill the distances of all reachable cells from the start.
+	///
+	/// Returns the distance for each cell index, None for unreachable cells.
+	fn distances_from_start(&self) -> Vec<Option<usize>>
+	{
+		self.distances_from(self.start)
+	}
+
+	/// Flood fill the distances of all reachable cells from the given position.
+	///
+	/// Returns the distance for each cell index, None for unreachable cells.
+	fn distances_from(&self, origin: usize) -> Vec<Option<usize>>
+	{
+		let mut distances: Vec<Option<usize>> = vec![None; self.cells.len()];
+		let mut queue: VecDeque<usize> = VecDeque::new();
+
+		distances[origin] = Some(0);
+		queue.push_back(origin);
+
+		while let Some(position) = queue.pop_front()
+		{
+			let distance = distances[position].unwrap_or(0);
+			for p in self.get_neighbours(position)
+			{
+				if distances[p].is_none()
+				{
+					distances[p] = Some(distance + 1);
+					queue.push_back(p);
+				}
+			}
+		}
+
+		distances
+	}
+
+	/// Find the longest of the shortest routes between any two cells.
+	///
+	/// Returns the cells of the route in order, or an empty vector for a
+	/// maze without passages. Only the part of the maze connected to the
+	/// first open cell is searched.
+	fn longest_route_positions(&self) -> Vec<usize>
+	{
+		let farthest = |distances: &Vec<Option<usize>>| {
+			distances.iter().enumerate().max_by_key(|(_, d)| **d).map(|(p, _)| p)
+		};
+
+		let first = match self.cells.iter().position(|c| c.celltype != MazeCellType::Wall)
+		{
+			Some(position) => position,
+			None => return Vec::new(),
+		};
+		let from = farthest(&self.distances_from(first)).unwrap_or(first);
+		let distances = self.distances_from(from);
+		let to = farthest(&distances).unwrap_or(from);
+
+		// walk back along decreasing distances
+		let mut route = vec![to];
+		let mut position = to;
+		while position != from
+		{
+			let previous = distances[position].map(|d| d - 1);
+			position = match self.get_neighbours(position).into_iter().find(|&p| distances[p] == previous)
+			{
+				Some(p) => p,
+				None => break,
+			};
+			route.push(position);
+		}
+		route.reverse();
+
+		route
+	}
+
+	/// Find the longest of the shortest routes between any two cells.
+	///
+	/// The length tells how difficult the maze is at most.
+	///
+	/// Returns the length of the route in steps and the coordinates of its cells.
+	pub fn longest_path(&self) -> (usize, Vec<(usize, usize)>)
+	{
+		let route = self.longest_route_positions();
+		let length = route.len().saturating_sub(1);
+		let coordinates = route.iter()
+			.map(|p| (p % self.dimensions.width, p / self.dimensions.width))
+			.collect();
+
+		(length, coordinates)
+	}
+
+	/// Move the start and the end to the ends of the longest route in the maze
+	///
+	/// The openings of the old start and end are walled up.
+	///
+	/// Returns AppError if the maze has no passages.
+	///
+	pub fn place_endpoints_at_extremes(&mut self) -> Result<(), AppError>
+	{
+		for old in [self.start, self.end]
+		{
+			if self.cells[old].celltype == MazeCellType::Start || self.cells[old].celltype == MazeCellType::End
+			{
+				self.cells[old].celltype = MazeCellType::Wall;
+			}
+		}
+
+		let route = self.longest_route_positions();
+		match (route.first(), route.last())
+		{
+			(Some(&start), Some(&end)) => self.set_endpoints(start, end),
+			_ => Err(AppError::new("Maze has no passages for the start and the end")),
+		}
+	}
+
+	/// Compute the distance of each reachable cell from the start.
+	///
+	/// Distances are flood filled from the start and written as the `distance`
+	/// of each cell. Distance of the walls and unreachable cells is cleared.
+	pub fn compute_distance_field(&mut self)
+	{
+		let distances = self.distances_from_start();
+
+		for (cell, distance) in self.cells.iter_mut().zip(distances.iter())
+		{
+			cell.distance = distance.map(|d| d as u32);
+		}
+	}
+
+	/// Compute the distance field like `compute_distance_field`, using
+	/// the threads of the current rayon thread pool.
+	///
+	/// The flood fill expands wide distance levels of cells in parallel and
+	/// the distances are stored to the cells in parallel. The result is
+	/// identical to the serial version. Paths in a maze are narrow, so only
+	/// mazes with loops and open areas gain from the parallel flood fill.
+	pub fn compute_distance_field_parallel(&mut self)
+	{
+		let distances = self.distances_from_parallel(self.start);
+
+		self.cells.par_iter_mut().zip(distances.par_iter()).for_each(|(cell, distance)| {
+			cell.distance = distance.map(|d| d as u32);
+		});
+	}
+
+	/// Flood fill the distances from the given position one level at a time,
+	/// finding the neighbors of the cells on each level in parallel.
+	fn distances_from_parallel(&self, origin: usize) -> Vec<Option<usize>>
+	{
+		let mut distances: Vec<Option<usize>> = vec![None; self.cells.len()];
+		let mut frontier: Vec<usize> = vec![origin];
+		let mut distance = 0;
+
+		distances[origin] = Some(0);
+		while !frontier.is_empty()
+		{
+			distance += 1;
+			let candidates: Vec<usize> = if frontier.len() < PARALLEL_FRONTIER_MIN
+			{
+				frontier.iter().flat_map(|&position| self.get_neighbours(position)).collect()
+			}
+			else
+			{
+				frontier.par_iter().flat_map_iter(|&position| self.get_neighbours(position)).collect()
+			};
+
+			frontier.clear();
+			for p in candidates
+			{
+				if distances[p].is_none()
+				{
+					distances[p] = Some(distance);
+					frontier.push(p);
+				}
+			}
+		}
+
+		distances
+	}
+
+	/// Check if there is a route from the start to the end.
+	///
+	/// Unlike the solvers, this doesn't modify the maze.
+	pub fn is_solvable(&self) -> bool
+	{
+		if self.cells[self.start].celltype == MazeCellType::Wall ||
+		   self.cells[self.end].celltype == MazeCellType::Wall
+		{
+			return false;
+		}
+
+		self.distances_from_start()[self.end].is_some()
+	}
+
+	/// Find the shortest route from the start to the end with a breadth-first search.
+	///
+	/// # Parameters
+	///
+	/// * `step`            - Process only one cell from the queue per call
+	///
+	/// Returns true when the search is finished and the route, if any, is marked.
+	pub fn run_bfs(&mut self, step: bool) -> bool
+	{
+		if self.mark_trivial_route()
+		{
+			return true;
+		}
+
+		// continue an ongoing search or start a new one
+		let mut state = match self.bfs.take()
+		{
+			Some(state) => state,
+			None => {
+				self.clear_route_marks();
+				let mut state = BfsState {
+					queue: VecDeque::new(),
+					parents: vec![None; self.cells.len()],
+				};
+				state.queue.push_back(self.start);
+				state
+			}
+		};
+
+		let mut total = 0;
+		if !step && self.progress.is_enabled()
+		{
+			total = self.open_cell_count();
+		}
+		let mut searched = 0;
+		self.progress.restart();
+
+		while let Some(position) = state.queue.pop_front()
+		{
+			if self.is_cancelled()
+			{
+				info!("Search cancelled");
+				return true;
+			}
+			self.cells[position].visited = true;
+			searched += 1;
+			if total > 0
+			{
+				self.progress.report(searched, total);
+			}
+
+			if position == self.end
+			{
+				let mut current = Some(position);
+				while let Some(p) = current
+				{
+					self.cells[p].on_route = true;
+					current = state.parents[p];
+				}
+				return true;
+			}
+
+			for p in self.get_neighbours(position)
+			{
+				if p != self.start && state.parents[p].is_none()
+				{
+					state.parents[p] = Some(position);
+					state.queue.push_back(p);
+				}
+			}
+
+			if step
+			{
+				self.bfs = Some(state);
+				return false;
+			}
+		}
+
+		info!("Breadth-first search found no route");
+		true
+	}
+
+	/// Walk from the start to the end keeping the right hand on the wall.
+	///
+	/// Turns right if possible, else goes straight, else turns left and
+	/// else turns back. Cells walked back from are removed from the route.
+	///
+	/// The end is always found in a maze without loops. If the walk repeats
+	/// its first move from the start, there is no route.
+	/// In a braided maze the walk may circle a loop forever, so it also
+	/// gives up after trying every direction in every cell.
+	///
+	/// # Parameters
+	///
+	/// * `step`            - Walk only one cell per call
+	///
+	/// Returns true when the walk is finished and the route, if any, is marked.
+	pub fn run_wall_follower(&mut self, step: bool) -> bool
+	{
+		if self.mark_trivial_route()
+		{
+			return true;
+		}
+
+		// continue an ongoing walk or start a new one
+		let mut state = match self.wall_follower.take()
+		{
+			Some(state) => state,
+			None => {
+				self.clear_route_marks();
+				let heading = match self.neighbors_with_directions(self.start).first()
+				{
+					Some((direction, _)) => *direction,
+					None => {
+						info!("Wall follower found no route");
+						return true;
+					},
+				};
+				self.cells[self.start].visited = true;
+				self.cells[self.start].on_route = true;
+				WallFollowerState {
+					route: vec![self.start],
+					heading,
+					first_move: None,
+					steps: 0,
+				}
+			}
+		};
+
+		let max_steps = NUM_OF_DIRECTIONS * self.cells.len();
+		while let Some(&position) = state.route.last()
+		{
+			if self.is_cancelled()
+			{
+				info!("Search cancelled");
+				return true;
+			}
+			if position == self.end
+			{
+				return true;
+			}
+			if state.steps >= max_steps
+			{
+				info!("Wall follower gave up after {} steps", max_steps);
+				for p in state.route
+				{
+					self.cells[p].on_route = false;
+				}
+				return true;
+			}
+
+			let heading = state.heading;
+			let turns = [heading.turn_right(), heading, heading.turn_left(), heading.get_opposite_direction()];
+			let (direction, next) = match turns.iter().find_map(|d| {
+				match self.get_neighboring_position(position, *d)
+				{
+					Ok(p) if self.cells[p].celltype != MazeCellType::Wall => Some((*d, p)),
+					_ => None,
+				}
+			})
+			{
+				Some(turn) => turn,
+				None => {
+					info!("Wall follower found no route");
+					return true;
+				},
+			};
+
+			if state.first_move == Some((direction, next))
+			{
+				// the walk repeats itself from here on
+				info!("Wall follower returned to the start, no route");
+				for p in state.route
+				{
+					self.cells[p].on_route = false;
+				}
+				return true;
+			}
+			if state.first_move.is_none()
+			{
+				state.first_move = Some((direction, next));
+			}
+			state.heading = direction;
+			state.steps += 1;
+			self.cells[next].visited = true;
+			if self.cells[next].on_route
+			{
+				// walked back, drop the cells after the one returned to
+				while let Some(&p) = state.route.last()
+				{
+					if p == next
+					{
+						break;
+					}
+					self.cells[p].on_route = false;
+					state.route.pop();
+				}
+			}
+			else
+			{
+				self.cells[next].on_route = true;
+				state.route.push(next);
+			}
+
+			if step
+			{
+				self.wall_follower = Some(state);
+				return false;
+			}
+		}
+
+		true
+	}
+
+	/// Cost of moving to the given cell
+	fn step_cost(&self, position: usize) -> usize
+	{
+		self.cells[position].weight as usize
+	}
+
+	/// Find the cheapest route from the start to the end with Dijkstra's algorithm.
+	///
+	/// # Parameters
+	///
+	/// * `step`            - Process only one cell from the queue per call
+	///
+	/// Returns true when the search is finished and the route, if any, is marked.
+	pub fn run_dijkstra(&mut self, step: bool) -> bool
+	{
+		if self.mark_trivial_route()
+		{
+			return true;
+		}
+
+		// continue an ongoing search or start a new one
+		let mut state = match self.dijkstra.take()
+		{
+			Some(state) => state,
+			None => {
+				self.clear_route_marks();
+				let mut state = DijkstraState {
+					queue: BinaryHeap::new(),
+					costs: vec![None; self.cells.len()],
+					parents: vec![None; self.cells.len()],
+				};
+				state.costs[self.start] = Some(0);
+				state.queue.push(Reverse((0, self.start)));
+				state
+			}
+		};
+
+		let mut total = 0;
+		if !step && self.progress.is_enabled()
+		{
+			total = self.open_cell_count();
+		}
+		let mut searched = 0;
+		self.progress.restart();
+
+		while let Some(Reverse((cost, position))) = state.queue.pop()
+		{
+			if self.is_cancelled()
+			{
+				info!("Search cancelled");
+				return true;
+			}
+			// skip entries made obsolete by a cheaper route found later
+			if self.cells[position].visited
+			{
+				continue;
+			}
+			self.cells[position].visited = true;
+			searched += 1;
+			if total > 0
+			{
+				self.progress.report(searched, total);
+			}
+
+			if position == self.end
+			{
+				let mut current = Some(position);
+				while let Some(p) = current
+				{
+					self.cells[p].on_route = true;
+					current = state.parents[p];
+				}
+				return true;
+			}
+
+			for p in self.get_neighbours(position)
+			{
+				let new_cost = cost + self.step_cost(p);
+				if state.costs[p].is_none_or(|c| new_cost < c)
+				{
+					state.costs[p] = Some(new_cost);
+					state.parents[p] = Some(position);
+					state.queue.push(Reverse((new_cost, p)));
+				}
+			}
+
+			if step
+			{
+				self.dijkstra = Some(state);
+				return false;
+			}
+		}
+
+		info!("Dijkstra's algorithm found no route");
+		true
+	}
+
+	pub fn run_graph_elimination(&mut self, step: bool) -> bool
+	{
+		if self.mark_trivial_route()
+		{
+			return false;
+		}
+
+		for i in self.dimensions.width..(self.cells.len() - self.dimensions.width)
+		{
+			// just for optimization, skip walls, start and end
+			if self.cells[i].celltype != MazeCellType::Passage
+			{
+				continue;
+			}
+
+			let mut leaf = Some(i);
+			while let Some(node) = leaf
+			{
+				// the start and the end stay even if only one corridor leads to them
+				if self.cells[node].celltype == MazeCellType::Passage &&
+				   self.get_num_of_graph_connections(node) == 1
+				{
+					leaf = self.remove_dead_end(node);
+
+					if step
+					{
+						info!("Graph elimination stepped");
+						return true;
+					}
+				}
+				else
+				{
+					leaf = None
+				}
+			}
+		}
+
+		info!("Graph elimination done");
+		return false;
+	}
+
+	/// Mark the route of a degenerate maze, where start and end are the same cell.
+	///
+	/// Returns true if the maze was degenerate and the route is now marked.
+	fn mark_trivial_route(&mut self) -> bool
+	{
+		if self.start != self.end
+		{
+			return false;
+		}
+
+		info!("Start and end are the same cell, nothing to solve");
+		self.cells[self.start].visited = true;
+		self.cells[self.start].on_route = true;
+		true
+	}
+
+	fn get_num_of_graph_connections(&mut self, position: usize) -> usize
+	{
+		let mut count = 0;
+
+		for i in 0..NUM_OF_DIRECTIONS
+		{
+			if self.cells[position].nodes[i] != None
+			{
+				count += 1;
+			}
+		}
+
+		count
+	}
+
+	fn remove_dead_end(&mut self, position: usize) -> Option<usize>
+	{
+		for i in 0..NUM_OF_DIRECTIONS
+		{
+			if self.cells[position].nodes[i] != None
+			{
+				if let Some(prev) = self.cells[position].nodes[i]
+				{
+					let prev = prev as usize;
+					let opposite = Direction::from_usize(i).get_opposite_direction();
+					self.cells[prev].nodes[opposite as usize] = None;
+					self.cells[position].nodes[i] = None;
+					return Some(prev);
+				}
+			}
+		}
+		None
+	}
+
+	/// Generate a topology graph of this maze.
+	///
+	/// The corridors leaving each node are followed only once, so
+	/// the graph is complete also in braided mazes with loops.
+	pub fn create_topology_graph(&mut self)
+	{
+		self.build_topology_graph(None);
+	}
+
+	/// Generate a topology graph of this maze, walking at most the given number of cells
+	///
+	/// # Parameters
+	///
+	/// * `max_steps`       - Maximum number of cells to walk, None for no limit
+	///
+	/// Returns the number of cells walked. The graph is left out and
+	/// `graph_created` stays false if the limit was reached.
+	fn build_topology_graph(&mut self, max_steps: Option<usize>) -> usize
+	{
+		let mut stack: Vec<(usize, usize, Direction)> = Vec::new();
+		let mut expanded: Vec<bool> = vec![false; self.cells.len()];
+
+		if self.start == self.end
+		{
+			// nothing to connect, the start is also the end
+			self.graph_created = true;
+			return 0;
+		}
+
+		// follow every corridor leaving the start
+		expanded[self.start] = true;
+		for (direction, _) in self.neighbors_with_directions(self.start)
+		{
+			stack.push((self.start, self.start, direction));
+		}
+
+		let mut steps = 0;
+		while let Some((previous, position, direction)) = stack.pop()
+		{
+			if max_steps.is_some_and(|max| steps >= max)
+			{
+				info!("Creating the topology graph stopped after {} steps", steps);
+				for cell in self.cells.iter_mut()
+				{
+					cell.nodes = [None; NUM_OF_DIRECTIONS];
+				}
+				return steps;
+			}
+			steps += 1;
+
+			let node_info = self.check_passage(position, direction);
+			match node_info.nodetype
+			{
+				GraphNodeType::Straight => {
+					stack.push((previous, node_info.position, direction));
+				},
+				GraphNodeType::Intersection => {
+					// a loop leads back to a node whose corridors are already followed
+					if !expanded[node_info.position]
+					{
+						expanded[node_info.position] = true;
+						for dir in node_info.directions.iter()
+						{
+							stack.push((node_info.position, node_info.position, *dir));
+						}
+					}
+					self.add_topology_node(previous, node_info.position, direction);
+				},
+				GraphNodeType::DeadEnd | GraphNodeType::Start => {
+					self.add_topology_node(previous, node_info.position, direction);
+				},
+				GraphNodeType::End => {
+					self.add_topology_node(previous, node_info.position, direction);
+					//break;
+				},
+				GraphNodeType::NA => {
+					debug!("Internal error. Invalid maze position encountered {}", position);
+					break;
+				},
+			}
+		}
+
+		self.graph_created = true;
+		steps
+	}
+
+	/// Mark the route through the topology graph from the start to the end.
+	///
+	/// Nodes of the graph are connected by straight corridors, so all
+	/// cells between two consecutive nodes on the route are marked.
+	///
+	/// Returns true if a route was found.
+	pub fn mark_graph_route(&mut self) -> bool
+	{
+		if !self.graph_created
+		{
+			return false;
+		}
+
+		self.clear_route_marks();
+
+		let mut parents: Vec<Option<usize>> = vec![None; self.cells.len()];
+		let mut queue: VecDeque<usize> = VecDeque::new();
+		queue.push_back(self.start);
+
+		while let Some(position) = queue.pop_front()
+		{
+			if position == self.end
+			{
+				break;
+			}
+			for &node in self.cells[position].nodes.iter().flatten()
+			{
+				let node = node as usize;
+				if node != self.start && parents[node].is_none()
+				{
+					parents[node] = Some(position);
+					queue.push_back(node);
+				}
+			}
+		}
+
+		if self.start != self.end && parents[self.end].is_none()
+		{
+			debug!("No route through the topology graph");
+			return false;
+		}
+
+		let mut position = self.end;
+		self.cells[position].on_route = true;
+		while let Some(parent) = parents[position]
+		{
+			let mut step = self.dimensions.width;
+			if parent / self.dimensions.width == position / self.dimensions.width
+			{
+				step = 1;
+			}
+
+			let first = std::cmp::min(parent, position);
+			let last = std::cmp::max(parent, position);
+			for p in (first..=last).step_by(step)
+			{
+				self.cells[p].on_route = true;
+			}
+			position = parent;
+		}
+
+		true
+	}
+
+	/// Get the positions of the nodes in the simplified topology graph
+	///
+	/// The topology graph has a node at every corner. The simplified
+	/// graph keeps only the start, the end, the intersections and the
+	/// dead ends, so corridors with corners in them become single edges.
+	///
+	/// Returns an empty vector if the topology graph has not been created.
+	pub fn graph_nodes(&self) -> Vec<usize>
+	{
+		if !self.graph_created
+		{
+			return Vec::new();
+		}
+
+		(0..self.cells.len()).filter(|&p| self.is_graph_node(p)).collect()
+	}
+
+	/// Get the edges of the simplified topology graph
+	///
+	/// Each edge is a `(node_a, node_b, length)` tuple, where the nodes
+	/// are positions returned by `graph_nodes` and the length is the
+	/// number of steps along the corridor between them. Every edge is
+	/// listed once.
+	///
+	/// Returns an empty vector if the topology graph has not been created.
+	pub fn graph_edges(&self) -> Vec<(usize, usize, usize)>
+	{
+		let mut edges: Vec<(usize, usize, usize)> = Vec::new();
+
+		for node in self.graph_nodes()
+		{
+			for (i, next) in self.cells[node].nodes.iter().enumerate()
+			{
+				let mut previous = node;
+				let mut position = match next
+				{
+					Some(next) => *next as usize,
+					None => continue,
+				};
+				let mut length = self.manhattan_distance(previous, position);
+
+				while !self.is_graph_node(position)
+				{
+					let next = self.cells[position].nodes.iter()
+						.flatten()
+						.map(|n| *n as usize)
+						.find(|n| *n != previous);
+					match next
+					{
+						Some(next) => {
+							length += self.manhattan_distance(position, next);
+							previous = position;
+							position = next;
+						},
+						None => break,
+					}
+				}
+
+				// each edge is found from both of its ends, keep only one
+				let arrival = self.cells[position].nodes.iter()
+					.position(|n| *n == Some(previous as u32))
+					.unwrap_or(i);
+				if node < position || (node == position && i < arrival)
+				{
+					edges.push((node, position, length));
+				}
+			}
+		}
+
+		edges
+	}
+
+	fn is_graph_node(&self, position: usize) -> bool
+	{
+		let connections = self.cells[position].nodes.iter().flatten().count();
+		connections > 0 && (connections != 2 || position == self.start || position == self.end)
+	}
+
+	fn check_passage(&self, position: usize, direction: Direction) -> GraphNodeInfo
+	{
+		let mut node_info = GraphNodeInfo {
+			position: 0,
+			nodetype: GraphNodeType::NA,
+			directions: Vec::new()
+		};
+
+		if let Ok(pos) = self.get_neighboring_position(position, direction)
+		{
+			if self.cells[pos].celltype == MazeCellType::Passage
+			{
+				let opposite_direction = direction.get_opposite_direction();
+				node_info.directions = self.get_possible_directions(pos, opposite_direction);
+
+				match node_info.directions.len()
+				{
+					0 => {
+						node_info.nodetype = GraphNodeType::DeadEnd;
+					},
+					1 => {
+						if node_info.directions[0] == direction
+						{
+							node_info.nodetype = GraphNodeType::Straight;
+						}
+						else
+						{
+							// a corner
+							node_info.nodetype = GraphNodeType::Intersection;
+						}
+					},
+					_ => {
+						node_info.nodetype = GraphNodeType::Intersection;
+					},
+				}
+				node_info.position = pos;
+			}
+			else if self.cells[pos].celltype == MazeCellType::End
+			{
+				node_info.position = pos;
+				node_info.nodetype = GraphNodeType::End;
+			}
+			else if self.cells[pos].celltype == MazeCellType::Start
+			{
+				// a loop back to the start, its corridors are already followed
+				node_info.position = pos;
+				node_info.nodetype = GraphNodeType::Start;
+			}
+		}
+		debug!("Topology: node_info position: {}, nodetype: {}, num directions: {}",
+		       node_info.position,
+		       node_info.nodetype as usize,
+		       node_info.directions.len());
+		return node_info;
+	}
+
+	// Get all possible directions to proceed
+	// (not including the direction given as parameter)
+	fn get_possible_directions(&self, position: usize, direction: Direction) -> Vec<Direction>
+	{
+		let mut directions: Vec<Direction> = Direction::get_directions().iter().cloned().collect();
+
+		// remove incoming direction from directions
+		if !Direction::remove_direction(&mut directions, direction)
+		{
+			debug!("Internal error. Removing incoming direction failed.");
+		}
+
+		let mut result = directions.clone();
+
+		// check other directions
+		for test_direction in directions
 		{
 			if let Ok(pos) = self.get_neighboring_position(position, test_direction)
 			{
-				if self.cells[pos].celltype != MazeCellType::Wall
+				if self.cells[pos].celltype == MazeCellType::Wall
+				{
+					Direction::remove_direction(&mut result, test_direction);
+				}
+			}
+			else
+			{
+				Direction::remove_direction(&mut result, test_direction);
+			}
+		}
+
+		result
+	}
+
+	fn add_topology_node(&mut self, start: usize, end: usize, direction: Direction)
+	{
+		debug!("Topology: adding node, start: {}, end: {}, direction: {}", start, end, direction);
+		self.cells[start].nodes[direction as usize] = Some(end as u32);
+		self.cells[end].nodes[direction.get_opposite_direction() as usize] = Some(start as u32);
+	}
+
+	/// Get the cells of a solved route in order, from the start to the end.
+	///
+	/// Returns None if the maze has not been solved.
+	pub(crate) fn route_positions(&self) -> Option<Vec<usize>>
+	{
+		if !self.cells[self.start].on_route
+		{
+			return None;
+		}
+
+		let mut route: Vec<usize> = vec![self.start];
+		let mut previous = self.start;
+		let mut position = self.start;
+
+		while position != self.end
+		{
+			let next = Direction::get_directions().iter()
+				.filter_map(|d| self.get_neighboring_position(position, *d).ok())
+				.find(|p| *p != previous && self.cells[*p].on_route)?;
+			previous = position;
+			position = next;
+			route.push(position);
+		}
+
+		Some(route)
+	}
+
+	/// Get the coordinates of a solved route in order, from the start to the end.
+	///
+	/// Returns an empty vector if the maze has not been solved.
+	pub fn solution_path(&self) -> Vec<(usize, usize)>
+	{
+		self.route_positions()
+			.unwrap_or_default()
+			.into_iter()
+			.map(|p| (p % self.dimensions.width, p / self.dimensions.width))
+			.collect()
+	}
+
+	/// Get the moves needed to walk a solved route from the start to the end.
+	///
+	/// Returns None if the maze has not been solved.
+	pub fn solution_moves(&self) -> Option<Vec<Direction>>
+	{
+		let route = self.route_positions()?;
+		let moves = route.windows(2).map(|w| {
+			if w[1] + self.dimensions.width == w[0]
+			{
+				Direction::North
+			}
+			else if w[0] + self.dimensions.width == w[1]
+			{
+				Direction::South
+			}
+			else if w[0] + 1 == w[1]
+			{
+				Direction::East
+			}
+			else
+			{
+				Direction::West
+			}
+		}).collect();
+
+		Some(moves)
+	}
+
+	/// Get the moves of a solved route as a string of U, D, L and R characters.
+	///
+	/// Returns None if the maze has not been solved.
+	pub fn solution_moves_string(&self) -> Option<String>
+	{
+		Some(self.solution_moves()?.iter().map(|d| d.to_move_char()).collect())
+	}
+
+	/// Get the number of steps on the solved route
+	///
+	/// Returns None if the maze has not been solved.
+	pub fn path_length(&self) -> Option<usize>
+	{
+		if !self.cells[self.end].on_route
+		{
+			return None;
+		}
+
+		Some(self.cells.iter().filter(|cell| cell.on_route).count() - 1)
+	}
+
+	/// Iterate over the cells on the solved route
+	///
+	/// Yields the coordinates and the cell, in no particular order.
+	/// Use `solution_path` to get the route in order.
+	pub fn route_cells(&self) -> impl Iterator<Item = (usize, usize, &MazeCell)>
+	{
+		let width = self.dimensions.width;
+		self.cells.iter()
+			.enumerate()
+			.filter(|(_, cell)| cell.on_route)
+			.map(move |(i, cell)| (i % width, i / width, cell))
+	}
+
+	/// Iterate mutably over the cells that are not walls
+	///
+	/// Yields the passages, the start and the end.
+	pub fn passages_iter_mut(&mut self) -> impl Iterator<Item = &mut MazeCell>
+	{
+		self.cells.iter_mut().filter(|cell| cell.celltype != MazeCellType::Wall)
+	}
+}
+
+impl<'a> IntoIterator for &'a Maze {
+	type Item = (usize, usize, usize, usize, &'a MazeCell);
+	type IntoIter = MazeGraphIterator<'a>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		let mut iter = MazeGraphIterator {
+			maze: self,
+			stack: Vec::new(),
+		};
+
+		// follow every connection leaving the start
+		for dir in Direction::get_directions()
+		{
+			if self.cells[self.start].nodes[dir as usize].is_some()
+			{
+				iter.stack.push((self.start, dir));
+			}
+		}
+
+		iter
+	}
+}
+
+pub struct MazeGraphIterator<'a>
+{
+	maze: &'a Maze,
+	stack: Vec<(usize, Direction)>,
+}
+
+impl<'a> Iterator for MazeGraphIterator<'a>
+{
+	type Item = (usize, usize, usize, usize, &'a MazeCell);
+	fn next(&mut self) -> Option<(usize, usize, usize, usize, &'a MazeCell)>
+	{
+		let mut new_position = 0;
+		if let Some((position, direction)) = self.stack.pop()
+		{
+			debug!("Iterator: popped position {}, direction {}", position, direction);
+			if let Some(pos) = self.maze.cells[position].nodes[direction as usize]
+			{
+				let pos = pos as usize;
+				new_position = pos;
+				for dir in Direction::get_directions()
 				{
-					neighbours.push(pos);
+					if (self.maze.cells[pos].nodes[dir as usize] != None) &&
+					   (dir != direction.get_opposite_direction())
+					{
+						self.stack.push((pos, dir));
+					}
 				}
 			}
+
+			let y = new_position / self.maze.dimensions.width;
+			let x = new_position % self.maze.dimensions.width;
+			let prev_y = position / self.maze.dimensions.width;
+			let prev_x = position % self.maze.dimensions.width;
+
+			return Some((prev_x, prev_y, x, y, &self.maze.cells[position]));
+		}
+		None
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn temp_filename(name: &str) -> String
+	{
+		let mut path = std::env::temp_dir();
+		path.push(format!("mazetool_{}_{}", std::process::id(), name));
+		path.to_str().unwrap().to_string()
+	}
+
+	/// A maze with a single winding route from the start to the end
+	fn small_maze() -> Maze
+	{
+		Maze::from_ascii("
+#S###
+#   #
+### #
+#   #
+#E###
+").unwrap()
+	}
+
+	/// A maze with a loop, the end is reached straight down or the long way around
+	fn loop_maze() -> Maze
+	{
+		Maze::from_ascii("
+#S#####
+#     #
+# ### #
+#     #
+#E#####
+").unwrap()
+	}
+
+	#[test]
+	fn failed_write_keeps_original_file()
+	{
+		let filename = temp_filename("failed_write.maze");
+		fs::write(&filename, "original").unwrap();
+
+		let result = Maze::write_atomically(&filename, "maze file", |file| {
+			write!(file, "partial")?;
+			Err(AppError::new("simulated write failure"))
+		});
+
+		assert!(result.is_err());
+		assert_eq!(fs::read_to_string(&filename).unwrap(), "original");
+		let name = Path::new(&filename).file_name().unwrap().to_string_lossy().to_string();
+		let leftovers = fs::read_dir(std::env::temp_dir()).unwrap()
+			.filter_map(|entry| entry.ok())
+			.map(|entry| entry.file_name().to_string_lossy().to_string())
+			.filter(|other| other.starts_with(&name) && other.ends_with(".tmp"))
+			.count();
+		assert_eq!(leftovers, 0);
+		fs::remove_file(&filename).unwrap();
+	}
+
+	#[test]
+	fn solve_with_start_as_end()
+	{
+		let mut maze = Maze::new();
+		let position = maze.randomize_start_position();
+		maze.cells[position].celltype = MazeCellType::Start;
+		maze.start = position;
+		maze.end = position;
+
+		assert!(maze.run_a_star(false));
+		assert!(!maze.run_graph_elimination(false));
+		let route: Vec<&MazeCell> = maze.cells.iter().filter(|c| c.on_route).collect();
+		assert_eq!(route.len(), 1);
+		assert!(maze.cells[position].on_route);
+	}
+
+	#[test]
+	fn solution_moves_lead_from_start_to_end()
+	{
+		let mut maze = small_maze();
+		assert!(maze.solution_moves().is_none());
+
+		for cell in maze.cells.iter_mut().filter(|c| c.celltype != MazeCellType::Wall)
+		{
+			cell.on_route = true;
+		}
+
+		let moves = maze.solution_moves().unwrap();
+		let route_length = maze.cells.iter().filter(|c| c.on_route).count();
+		assert_eq!(moves.len(), route_length - 1);
+		assert_eq!(maze.solution_moves_string().unwrap(), "DRRDDLLD");
+
+		let mut position = maze.start;
+		for direction in moves
+		{
+			position = maze.get_neighboring_position(position, direction).unwrap();
+		}
+		assert_eq!(position, maze.end);
+	}
+
+	#[test]
+	fn passage_adjacency_lists_open_neighbours()
+	{
+		let maze = small_maze();
+		let adjacency = maze.passage_adjacency();
+
+		assert_eq!(adjacency.len(), 25);
+		assert!(adjacency[0].is_empty());
+		assert_eq!(adjacency[1], vec![6]);
+		assert_eq!(adjacency[7], vec![8, 6]);
+		assert_eq!(adjacency[8], vec![7, 13]);
+		assert_eq!(adjacency[16], vec![17, 21]);
+	}
+
+	#[test]
+	fn neighbors_are_paired_with_directions()
+	{
+		let maze = small_maze();
+
+		assert_eq!(maze.neighbors_with_directions(1), vec![(Direction::South, 6)]);
+		assert_eq!(maze.neighbors_with_directions(7), vec![(Direction::East, 8), (Direction::West, 6)]);
+		assert_eq!(maze.neighbors_with_directions(8), vec![(Direction::West, 7), (Direction::South, 13)]);
+		assert_eq!(maze.neighbors_with_directions(21), vec![(Direction::North, 16)]);
+	}
+
+	#[test]
+	fn generate_maze_with_passages()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 19, height: 19 }, GenMethod::Backtracker).unwrap();
+
+		assert!(maze.cells.iter().filter(|c| c.celltype == MazeCellType::Passage).count() > 0);
+		assert_eq!(maze.cells[maze.start].celltype, MazeCellType::Start);
+		assert_eq!(maze.cells[maze.end].celltype, MazeCellType::End);
+		assert_eq!(maze.cells.iter().filter(|c| c.celltype == MazeCellType::Start).count(), 1);
+		assert_eq!(maze.cells.iter().filter(|c| c.celltype == MazeCellType::End).count(), 1);
+	}
+
+	#[test]
+	fn generate_with_same_seed_is_identical()
+	{
+		let dimensions = Dimensions { width: 31, height: 21 };
+		let mut outputs: Vec<Vec<u8>> = Vec::new();
+
+		for _ in 0..2
+		{
+			let mut maze = Maze::new();
+			maze.set_seed(42);
+			maze.generate(dimensions, GenMethod::Backtracker).unwrap();
+
+			let mut output: Vec<u8> = Vec::new();
+			maze.write_maze(&mut output).unwrap();
+			outputs.push(output);
+		}
+
+		assert_eq!(outputs[0], outputs[1]);
+	}
+
+	#[test]
+	fn read_back_written_maze()
+	{
+		let filename = temp_filename("round_trip.maze");
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 15 }, GenMethod::Backtracker).unwrap();
+		maze.write_to_file(&filename).unwrap();
+
+		let mut loaded = Maze::new();
+		loaded.read_from_file(&filename).unwrap();
+		fs::remove_file(&filename).unwrap();
+
+		assert_eq!(loaded.dimensions.width, 21);
+		assert_eq!(loaded.dimensions.height, 15);
+		assert_eq!(loaded.start, maze.start);
+		assert_eq!(loaded.end, maze.end);
+		assert!(loaded.cells.iter().zip(maze.cells.iter()).all(|(a, b)| a.celltype == b.celltype));
+	}
+
+	#[test]
+	fn read_back_compressed_maze()
+	{
+		let filename = temp_filename("round_trip_gz.maze");
+		let compressed_filename = temp_filename("round_trip_gz.maze.gz");
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 101, height: 101 }, GenMethod::Kruskal).unwrap();
+		maze.write_to_file(&filename).unwrap();
+		maze.write_to_file(&compressed_filename).unwrap();
+
+		let size = fs::metadata(&filename).unwrap().len();
+		let compressed_size = fs::metadata(&compressed_filename).unwrap().len();
+		assert!(compressed_size < size / 4);
+		assert_eq!(&fs::read(&compressed_filename).unwrap()[..2], &[0x1f, 0x8b]);
+
+		let mut loaded = Maze::new();
+		loaded.read_from_file(&filename).unwrap();
+		let mut decompressed = Maze::new();
+		decompressed.read_from_file(&compressed_filename).unwrap();
+		fs::remove_file(&filename).unwrap();
+		fs::remove_file(&compressed_filename).unwrap();
+
+		assert_eq!(decompressed.to_string(), loaded.to_string());
+		assert_eq!(decompressed.start, loaded.start);
+		assert_eq!(decompressed.end, loaded.end);
+	}
+
+	#[test]
+	fn solve_two_mazes_with_a_star()
+	{
+		let mut first = Maze::new();
+		first.set_seed(1);
+		first.generate(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker).unwrap();
+		let mut second = Maze::new();
+		second.set_seed(2);
+		second.generate(Dimensions { width: 31, height: 15 }, GenMethod::Backtracker).unwrap();
+
+		for maze in [&mut first, &mut second]
+		{
+			assert!(maze.run_a_star(false));
+			assert!(maze.cells[maze.start].on_route);
+			assert!(maze.cells[maze.end].on_route);
+			assert!(maze.solution_moves().is_some());
 		}
+	}
 
-		neighbours
+	#[test]
+	fn bfs_finds_the_shortest_route()
+	{
+		let mut maze = Maze::from_ascii("
+#S###
+#   #
+# # #
+#   #
+###E#
+").unwrap();
+
+		while !maze.run_bfs(true) {}
+
+		assert_eq!(maze.solution_moves_string().unwrap(), "DRRDDD");
+		assert!(!maze.cells[16].on_route);
 	}
 
-	fn convert_position_to_coordinates(&self, position: usize) -> Dimensions
+	#[test]
+	fn distance_field_starts_from_zero()
 	{
-		let x = position / self.dimensions.width;
-		let y = position % self.dimensions.width;
+		let mut maze = small_maze();
+		maze.compute_distance_field();
 
-		Dimensions { width: x, height: y }
+		assert_eq!(maze.cells[maze.start].distance, Some(0));
+		assert_eq!(maze.cells[8].text(), "3");
+		assert_eq!(maze.cells[maze.end].distance, Some(8));
+		assert_eq!(maze.cells[0].distance, None);
 	}
 
-	fn manhattan_distance(&self, x: usize, y: usize) -> usize
+	#[test]
+	fn json_round_trip()
 	{
-		let a = self.convert_position_to_coordinates(x);
-		let b = self.convert_position_to_coordinates(y);
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 15, height: 11 }, GenMethod::Backtracker).unwrap();
+		maze.create_topology_graph();
+		maze.run_bfs(false);
+		maze.compute_distance_field();
+		let json = maze.to_json().unwrap();
 
-		let v = i32::abs(a.height as i32 - b.height as i32) as usize;
-		let h = i32::abs(a.width as i32 - b.width as i32) as usize;
+		let mut loaded = Maze::new();
+		loaded.from_json(&json).unwrap();
 
-		return v + h;
+		assert_eq!(loaded.dimensions.width, 15);
+		assert_eq!(loaded.dimensions.height, 11);
+		assert_eq!(loaded.start, maze.start);
+		assert_eq!(loaded.end, maze.end);
+		assert!(loaded.graph_created);
+		assert_eq!(loaded.to_json().unwrap(), json);
 	}
 
-	pub fn run_a_star(&mut self, step: bool) -> bool
+	#[test]
+	fn generate_with_all_methods()
 	{
-		#[derive(Clone, Copy, Eq, PartialEq, Debug)]
-		struct ListItem
+		for method in [GenMethod::Backtracker, GenMethod::Prim, GenMethod::Kruskal, GenMethod::AldousBroder]
 		{
-			position: usize,
-			parent: usize,
-			f: usize,
-			g: usize,
-			h: usize,
+			let mut maze = Maze::new();
+			maze.set_seed(7);
+			maze.generate(Dimensions { width: 21, height: 21 }, method).unwrap();
+
+			assert!(maze.cells.iter().any(|c| c.celltype == MazeCellType::Passage));
+			maze.run_bfs(false);
+			assert!(maze.cells[maze.end].on_route, "{:?} maze has no route", method);
 		}
+	}
 
-		impl Ord for ListItem {
-			fn cmp(&self, other: &Self) -> Ordering {
-				if self.f == other.f
-				{
-					Ordering::Equal
-				}
-				else if self.f > other.f
-				{
-					Ordering::Greater
-				}
-				else
-				{
-					Ordering::Less
-				}
-			}
+	#[test]
+	fn generate_with_each_entry_point()
+	{
+		let dimensions = Dimensions { width: 21, height: 15 };
+		let entry_points: [(GenMethod, fn(&mut Maze) -> Result<(), AppError>); 3] = [
+			(GenMethod::Backtracker, Maze::generate_backtracker),
+			(GenMethod::Prim, Maze::generate_prim),
+			(GenMethod::Kruskal, Maze::generate_kruskal),
+		];
+		for (method, generate) in entry_points
+		{
+			let mut expected = Maze::new();
+			expected.set_seed(5);
+			expected.generate(dimensions, method).unwrap();
+
+			let mut maze = Maze::with_dimensions(dimensions);
+			maze.set_seed(5);
+			generate(&mut maze).unwrap();
+			assert_eq!(maze.to_string(), expected.to_string(), "{:?}", method);
 		}
+	}
+
+	#[test]
+	fn even_dimensions_are_rounded_up()
+	{
+		let dimensions = Dimensions { width: 20, height: 19 }.normalize();
+
+		assert_eq!(dimensions.width, 21);
+		assert_eq!(dimensions.height, 19);
+		assert_eq!(Dimensions { width: MAZE_DIMENSION_MAX, height: 11 }.normalize().width,
+		           MAZE_DIMENSION_MAX - 1);
+	}
+
+	#[test]
+	fn get_cell_by_coordinates()
+	{
+		let mut maze = small_maze();
+
+		assert_eq!(maze.get(1, 0).unwrap().celltype, MazeCellType::Start);
+		assert_eq!(maze.get(3, 2).unwrap().celltype, MazeCellType::Passage);
+		assert!(maze.get(5, 5).is_none());
+		assert!(maze.get(5, 0).is_none());
+		assert!(maze.get_mut(0, 5).is_none());
+
+		maze.get_mut(2, 2).unwrap().celltype = MazeCellType::Passage;
+		assert_eq!(maze.cells[12].celltype, MazeCellType::Passage);
+	}
 
-		impl PartialOrd for ListItem {
-			fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-				Some(self.cmp(other))
+	#[test]
+	fn carved_maze_is_solvable()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker).unwrap();
+
+		assert!(maze.is_solvable());
+		assert!(!maze.cells.iter().any(|c| c.visited || c.on_route));
+	}
+
+	#[test]
+	fn wall_maze_is_unsolvable()
+	{
+		let mut maze = Maze::new();
+		maze.reset(Dimensions { width: 21, height: 21 }).unwrap();
+
+		assert!(!maze.is_solvable());
+	}
+
+	#[test]
+	fn move_endpoints_and_solve()
+	{
+		let mut maze = small_maze();
+		maze.run_bfs(false);
+
+		maze.set_endpoints(6, 18).unwrap();
+
+		assert_eq!(maze.cells[1].celltype, MazeCellType::Passage);
+		assert_eq!(maze.cells[21].celltype, MazeCellType::Passage);
+		assert_eq!(maze.cells[6].celltype, MazeCellType::Start);
+		assert_eq!(maze.cells[18].celltype, MazeCellType::End);
+		assert!(maze.cells.iter().all(|c| !c.visited && !c.on_route));
+		maze.run_bfs(false);
+		assert_eq!(maze.route_positions(), Some(vec![6, 7, 8, 13, 18]));
+	}
+
+	#[test]
+	fn endpoints_connect_to_the_inside()
+	{
+		for method in [GenMethod::Backtracker, GenMethod::Prim, GenMethod::Kruskal, GenMethod::AldousBroder]
+		{
+			for seed in 0..10
+			{
+				let mut maze = Maze::new();
+				maze.set_seed(seed);
+				maze.generate(Dimensions { width: 15, height: 11 }, method).unwrap();
+				assert!(!maze.get_neighbours(maze.start).is_empty());
+				assert!(!maze.get_neighbours(maze.end).is_empty());
 			}
 		}
 
-		static mut OPEN_LIST: BinaryHeap<ListItem, Min, MAX_HEAP_SIZE> = BinaryHeap::new();
-		static mut CLOSED_LIST: Vec<ListItem> = Vec::new();
+		let mut maze = Maze::from_ascii("
+#S###
+#   #
+#####
+#   #
+###E#
+").unwrap();
+		maze.open_edge_passage(maze.end);
+		assert_eq!(maze.cells[18].celltype, MazeCellType::Passage);
+		maze.open_edge_passage(maze.start);
+		assert_eq!(maze.cells[11].celltype, MazeCellType::Wall);
+	}
+
+	#[test]
+	fn solve_reports_no_path_and_time_out()
+	{
+		let mut maze = Maze::from_ascii("
+#S###
+#   #
+#####
+#   #
+#E###
+").unwrap();
+		let cap = Some(NUM_OF_DIRECTIONS * maze.cells.len());
+		for method in [SolveMethod::GraphOnly, SolveMethod::GraphElimination, SolveMethod::AStar,
+		               SolveMethod::Bfs, SolveMethod::Dijkstra, SolveMethod::WallFollower]
+		{
+			assert_eq!(maze.solve(method, cap), SolveResult::NoPath, "{}", method);
+		}
+
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker).unwrap();
+		let cap = Some(NUM_OF_DIRECTIONS * maze.cells.len());
+		for method in [SolveMethod::GraphOnly, SolveMethod::GraphElimination, SolveMethod::AStar,
+		               SolveMethod::Bfs, SolveMethod::Dijkstra, SolveMethod::WallFollower]
+		{
+			maze.clear_solution();
+			assert_eq!(maze.solve(method, cap), SolveResult::Solved);
+		}
 
-		let mut finished = false;
+		assert_eq!(maze.solve(SolveMethod::Bfs, Some(3)), SolveResult::TimedOut);
+		assert!(maze.cells.iter().all(|c| !c.visited && !c.on_route));
+	}
+
+	#[test]
+	fn solve_braided_maze_within_step_limit()
+	{
+		let mut braided = Maze::new();
+		braided.set_seed(0);
+		braided.generate(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker).unwrap();
+		braided.braid_randomly(1.0);
+		let cap = Some(NUM_OF_DIRECTIONS * braided.cells.len());
 
-		unsafe
+		for method in [SolveMethod::GraphOnly, SolveMethod::GraphElimination, SolveMethod::AStar,
+		               SolveMethod::Bfs, SolveMethod::Dijkstra, SolveMethod::WallFollower]
 		{
-			if OPEN_LIST.len() == 0
+			let mut maze = braided.clone();
+			let result = maze.solve(method, cap);
+			// the wall follower may circle a loop until it gives up
+			if method == SolveMethod::WallFollower
 			{
-				let start: ListItem = ListItem { position: self.start, parent: 0, f: 0, g: 0, h: 0 };
-				match OPEN_LIST.push(start)
-				{
-					Ok(_) => {},
-					Err(_) => {},
-				}
-				CLOSED_LIST.push(start);
+				assert_ne!(result, SolveResult::TimedOut);
 			}
-
-			while OPEN_LIST.len() > 0
+			else
 			{
-				let item = OPEN_LIST.pop().unwrap();
+				assert_eq!(result, SolveResult::Solved, "{}", method);
+			}
 
-				self.cells[item.position].visited = true;
+			let mut maze = braided.clone();
+			assert_eq!(maze.solve(method, Some(10)), SolveResult::TimedOut, "{}", method);
+			assert!(!maze.graph_created);
+		}
+	}
+
+	#[test]
+	fn solve_with_endpoints_inside_the_maze()
+	{
+		let dimensions = Dimensions { width: 21, height: 21 };
+		for seed in 0..5
+		{
+			let mut generated = Maze::new();
+			generated.set_seed(seed);
+			generated.generate(dimensions, GenMethod::Prim).unwrap();
+			let mut braided = generated.clone();
+			braided.braid_randomly(1.0);
+
+			for (mut maze, loops) in [(generated, false), (braided, true)]
+			{
+				// rooms in the middle of the maze and next to its left side
+				maze.set_endpoints(9 * 21 + 9, 19 * 21 + 1).unwrap();
+				maze.run_bfs(false);
+				let length = maze.path_length();
+				assert!(length.is_some());
 
-				let mut successors : Vec<ListItem> = Vec::new();
-				for p in self.get_neighbours(item.position)
+				for method in [SolveMethod::GraphOnly, SolveMethod::GraphElimination, SolveMethod::AStar,
+				               SolveMethod::Bfs, SolveMethod::Dijkstra, SolveMethod::WallFollower]
 				{
-					if p != item.parent
+					maze.clear_solution();
+					let result = maze.solve(method, None);
+					if method == SolveMethod::WallFollower
+					{
+						// the wall follower may circle a loop until it gives up
+						assert!(loops || result == SolveResult::Solved, "seed {}", seed);
+						continue;
+					}
+					assert_eq!(result, SolveResult::Solved, "{} seed {}", method, seed);
+					// the only route of a maze without loops is also the shortest
+					if !loops
 					{
-						successors.push(ListItem {
-							position: p,
-							parent: item.position,
-							f: 0,
-							g: item.g + 1,
-							h: self.manhattan_distance(p, self.end) });
+						assert_eq!(maze.path_length(), length, "{} seed {}", method, seed);
 					}
 				}
 
-				while let Some(mut s) = successors.pop()
-				{
-					//s.f = s.g + (2 * s.h); // weighted to prefer routes closer to exit
-					s.f = s.g + s.h;
+				maze.set_start(11, 11).unwrap();
+				maze.clear_solution();
+				assert_eq!(maze.solve(SolveMethod::GraphOnly, None), SolveResult::Solved, "seed {}", seed);
+			}
+		}
+	}
 
-					if self.cells[s.position].celltype == MazeCellType::End
-					{
-						self.cells[s.position].visited = true;
-						CLOSED_LIST.push(s);
+	#[test]
+	fn move_start_and_end_separately()
+	{
+		let mut maze = small_maze();
 
-						// only one route through the maze, no need to continue
-						OPEN_LIST.clear();
-						break;
-					}
+		maze.set_start(3, 1).unwrap();
+		assert_eq!(maze.cells[1].celltype, MazeCellType::Passage);
+		assert_eq!(maze.cells[8].celltype, MazeCellType::Start);
+		assert_eq!(maze.start, 8);
 
-					self.cells[s.position].text = format!("{}", s.h).to_string();
-					CLOSED_LIST.push(s);
+		maze.set_end(1, 3).unwrap();
+		assert_eq!(maze.cells[21].celltype, MazeCellType::Passage);
+		assert_eq!(maze.cells[16].celltype, MazeCellType::End);
+		assert_eq!(maze.end, 16);
 
-					if let Some(_old) = OPEN_LIST.iter().find(|x| (x.position == s.position) && (x.f < s.f))
-					{
-						// skip, there is already a shorter way to get there
-						continue;
-					}
+		maze.run_bfs(false);
+		assert_eq!(maze.route_positions(), Some(vec![8, 13, 18, 17, 16]));
+
+		assert!(maze.set_start(0, 0).is_err());
+		assert!(maze.set_end(5, 1).is_err());
+		assert!(maze.set_start(1, 3).is_err());
+		assert_eq!(maze.start, 8);
+	}
+
+	#[test]
+	fn endpoints_on_walls_are_rejected()
+	{
+		let mut maze = small_maze();
+
+		assert!(maze.set_endpoints(0, 18).is_err());
+		assert!(maze.set_endpoints(6, 25).is_err());
+		assert_eq!(maze.start, 1);
+		assert_eq!(maze.end, 21);
+	}
+
+	#[test]
+	fn braiding_removes_dead_ends()
+	{
+		let mut maze = Maze::new();
+		maze.set_seed(7);
+		maze.generate(Dimensions { width: 31, height: 31 }, GenMethod::Backtracker).unwrap();
+		let dead_ends = maze.dead_ends().len();
+		assert!(dead_ends > 0);
+
+		maze.braid(1.0, &mut StdRng::seed_from_u64(7));
+
+		assert!(maze.dead_ends().len() < dead_ends / 4);
+		assert!(maze.is_solvable());
+	}
+
+	#[test]
+	fn topology_graph_of_braided_maze()
+	{
+		let mut maze = Maze::new();
+		maze.set_seed(0);
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker).unwrap();
+		maze.braid_randomly(1.0);
+
+		maze.create_topology_graph();
+
+		assert!(maze.graph_created);
+		let edges = maze.graph_edges();
+		// loops make the graph have at least as many edges as nodes
+		assert!(edges.len() >= maze.graph_nodes().len());
+		assert!(edges.iter().any(|&(a, b, _)| a == maze.end || b == maze.end));
+		assert!(maze.to_dot().contains(&format!("n{} [", maze.end)));
+		assert!(maze.mark_graph_route());
+		let route = maze.route_positions().unwrap();
+		assert_eq!(route.first(), Some(&maze.start));
+		assert_eq!(route.last(), Some(&maze.end));
+	}
+
+	#[test]
+	fn braiding_with_zero_ratio_keeps_maze()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Prim).unwrap();
+		let adjacency = maze.passage_adjacency();
+
+		maze.braid(0.0, &mut StdRng::seed_from_u64(1));
+
+		assert_eq!(maze.passage_adjacency(), adjacency);
+	}
+
+	#[test]
+	fn dijkstra_route_is_as_short_as_bfs()
+	{
+		for method in [GenMethod::Backtracker, GenMethod::Prim, GenMethod::Kruskal]
+		{
+			let mut maze = Maze::new();
+			maze.generate(Dimensions { width: 31, height: 21 }, method).unwrap();
+			maze.braid(0.5, &mut StdRng::seed_from_u64(3));
+			let mut bfs_maze = maze.clone();
+
+			while !maze.run_dijkstra(true) {}
+			bfs_maze.run_bfs(false);
+
+			assert_eq!(maze.solution_moves().unwrap().len(), bfs_maze.solution_moves().unwrap().len());
+		}
+	}
+
+	#[test]
+	fn solvers_avoid_heavy_corridor()
+	{
+		let mut maze = loop_maze();
+		maze.cells[15].weight = 10;
+		let mut a_star_maze = maze.clone();
+
+		maze.run_dijkstra(false);
+		a_star_maze.run_a_star(false);
+
+		for m in [&maze, &a_star_maze]
+		{
+			assert!(!m.cells[15].on_route);
+			assert!(m.cells[19].on_route);
+		}
+
+		let mut light = loop_maze();
+		light.run_dijkstra(false);
+		assert!(light.cells[15].on_route);
+	}
+
+	#[test]
+	fn a_star_takes_the_cheaper_of_two_routes_to_the_end()
+	{
+		let mut maze = Maze::from_ascii("
+#S#########
+#         #
+# ####### #
+#E####### #
+# ####### #
+#         #
+###########
+###########
+###########
+###########
+###########
+").unwrap();
+		// the end is first reached from above, but the way around costs less
+		maze.set_weight_rect(0, 0, 10, 10, 0);
+		maze.cells[12].weight = 1;
+		maze.cells[23].weight = 1;
+		let mut dijkstra_maze = maze.clone();
+
+		assert_eq!(maze.solve(SolveMethod::AStar, None), SolveResult::Solved);
+		assert_eq!(dijkstra_maze.solve(SolveMethod::Dijkstra, None), SolveResult::Solved);
+
+		for m in [&maze, &dijkstra_maze]
+		{
+			assert!(!m.cells[23].on_route);
+			assert!(m.cells[45].on_route);
+		}
+	}
+
+	#[test]
+	fn dijkstra_avoids_heavy_region_when_cheaper()
+	{
+		// the way around is 12 steps, through the region 3 + weight
+		let mut maze = loop_maze();
+		maze.set_weight_rect(0, 2, 2, 2, 10);
+		assert_eq!(maze.cells[15].weight, 10);
+		assert_eq!(maze.cells[14].weight, DEFAULT_WEIGHT);
+		maze.run_dijkstra(false);
+		assert!(!maze.cells[15].on_route);
+		assert!(maze.cells[19].on_route);
+
+		let mut maze = loop_maze();
+		maze.set_weight_rect(2, 2, 0, 2, 3);
+		maze.run_dijkstra(false);
+		assert!(maze.cells[15].on_route);
+		assert!(!maze.cells[19].on_route);
+
+		// clamped to the maze, walls keep their weight
+		maze.set_weight_rect(4, 1, 100, 100, 7);
+		for (position, cell) in maze.cells.iter().enumerate()
+		{
+			let inside = position % 7 >= 4 && position / 7 >= 1;
+			let expected = if inside && cell.celltype != MazeCellType::Wall { 7 } else { DEFAULT_WEIGHT };
+			assert_eq!(cell.weight, if position == 15 { 3 } else { expected });
+		}
+	}
+
+	#[test]
+	fn solvers_report_stats()
+	{
+		let mut maze = Maze::new();
+		maze.set_seed(5);
+		maze.generate(Dimensions { width: 41, height: 41 }, GenMethod::Prim).unwrap();
+		assert_eq!(maze.solve_stats(), None);
+
+		let mut stats = Vec::new();
+		for method in [SolveMethod::AStar, SolveMethod::Bfs, SolveMethod::Dijkstra]
+		{
+			assert_eq!(maze.solve(method, None), SolveResult::Solved);
+			let s = maze.solve_stats().unwrap();
+			assert!(s.steps > 0);
+			assert_eq!(Some(s.path_len), maze.path_length());
+			assert!(s.cells_visited > s.path_len && s.cells_visited <= maze.open_cell_count());
+			stats.push(s);
+		}
+		assert_eq!(stats[0].path_len, stats[1].path_len);
+		assert!(stats[0].cells_visited <= stats[1].cells_visited);
+
+		maze.clear_solution();
+		assert_eq!(maze.solve_stats(), None);
+		assert_eq!(maze.solve(SolveMethod::Bfs, Some(3)), SolveResult::TimedOut);
+		assert_eq!(maze.solve_stats(), None);
+	}
+
+	#[test]
+	fn read_back_written_weights()
+	{
+		let filename = temp_filename("weights.maze");
+		let mut maze = small_maze();
+		maze.cells[7].weight = 5;
+		maze.write_to_file(&filename).unwrap();
+
+		let contents = std::fs::read_to_string(&filename).unwrap();
+		assert_eq!(contents.lines().filter(|l| l.starts_with("W ")).count(), 1);
+
+		let mut read = Maze::new();
+		read.read_from_file(&filename).unwrap();
+		std::fs::remove_file(&filename).unwrap();
+
+		let weights: Vec<u32> = read.cells.iter().map(|c| c.weight).collect();
+		let mut expected = vec![1; 25];
+		expected[7] = 5;
+		assert_eq!(weights, expected);
+	}
+
+	#[test]
+	fn solution_path_goes_from_start_to_end()
+	{
+		let mut maze = small_maze();
+		assert!(maze.solution_path().is_empty());
+
+		maze.run_bfs(false);
+
+		assert_eq!(maze.solution_path(), vec![(1, 0), (1, 1), (2, 1), (3, 1), (3, 2),
+		                                      (3, 3), (2, 3), (1, 3), (1, 4)]);
+	}
+
+	#[test]
+	fn a_star_solves_large_maze()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 99, height: 99 }, GenMethod::Prim).unwrap();
+
+		assert!(maze.run_a_star(false));
+
+		assert!(maze.cells[maze.end].on_route);
+		assert!(maze.cells[maze.start].on_route);
+	}
+
+	#[test]
+	fn read_uses_header_dimensions()
+	{
+		let filename = temp_filename("header.maze");
+		fs::write(&filename, "Maze 7 5\n█S█████\n█     █\n█ ███ █\n█     █\n█████E█\n").unwrap();
+
+		let mut maze = Maze::new();
+		maze.read_from_file(&filename).unwrap();
+		fs::remove_file(&filename).unwrap();
+
+		assert_eq!(maze.dimensions.width, 7);
+		assert_eq!(maze.dimensions.height, 5);
+		assert_eq!(maze.cells.len(), 35);
+		assert_eq!(maze.start, 1);
+		assert_eq!(maze.end, 33);
+	}
+
+	#[test]
+	fn neighbors_stay_inside_the_maze()
+	{
+		let mut maze = Maze::new();
+		maze.reset(Dimensions { width: 10, height: 10 }).unwrap();
+		let neighbor = |position: usize, direction: Direction| maze.get_neighboring_position(position, direction).ok();
+
+		// corners
+		assert_eq!(Direction::get_directions().map(|d| neighbor(0, d)), [None, Some(1), None, Some(10)]);
+		assert_eq!(Direction::get_directions().map(|d| neighbor(9, d)), [None, None, Some(8), Some(19)]);
+		assert_eq!(Direction::get_directions().map(|d| neighbor(90, d)), [Some(80), Some(91), None, None]);
+		assert_eq!(Direction::get_directions().map(|d| neighbor(99, d)), [Some(89), None, Some(98), None]);
+
+		// edges
+		for i in 1..9
+		{
+			assert_eq!(neighbor(i, Direction::North), None);
+			assert_eq!(neighbor(i * 10, Direction::West), None);
+			assert_eq!(neighbor(i * 10, Direction::North), Some((i - 1) * 10));
+			assert_eq!(neighbor(i * 10 + 9, Direction::East), None);
+			assert_eq!(neighbor(i * 10 + 9, Direction::West), Some(i * 10 + 8));
+			assert_eq!(neighbor(90 + i, Direction::South), None);
+			assert_eq!(neighbor(90 + i, Direction::North), Some(80 + i));
+		}
+
+		assert_eq!(neighbor(100, Direction::North), None);
+	}
+
+	#[test]
+	fn count_dead_ends_and_passages()
+	{
+		let maze = Maze::from_ascii("
+#S#####
+#   # #
+# ### #
+#     #
+### ###
+#   ###
+#E#####
+").unwrap();
+
+		assert_eq!(maze.count_passages(), 15);
+		// (3,1) and (5,1)
+		assert_eq!(maze.count_dead_ends(), 2);
+	}
+
+	#[test]
+	fn longest_path_of_corridor()
+	{
+		let maze = Maze::from_ascii("
+#########
+#       #
+#########
+").unwrap();
+
+		let (length, path) = maze.longest_path();
+
+		assert_eq!(length, 6);
+		assert_eq!(path.len(), 7);
+		assert!(path.contains(&(1, 1)) && path.contains(&(7, 1)));
+		assert!(path.first() == Some(&(1, 1)) || path.first() == Some(&(7, 1)));
+	}
+
+	#[test]
+	fn endpoints_at_extremes_make_route_longest()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Kruskal).unwrap();
+		maze.place_endpoints_at_extremes().unwrap();
+
+		// walling up the old openings may shorten the longest route
+		let (length, _) = maze.longest_path();
+		maze.run_bfs(false);
+
+		assert_eq!(maze.solution_moves().unwrap().len(), length);
+	}
+
+	#[test]
+	fn clear_and_solve_again()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker).unwrap();
+		maze.compute_distance_field();
+		maze.run_bfs(false);
+		let route = maze.solution_path();
+
+		maze.clear_solution();
+
+		assert!(maze.cells.iter().all(|c| !c.visited && !c.on_route && c.distance.is_none()));
+		assert!(maze.solution_path().is_empty());
+		maze.run_a_star(false);
+		assert_eq!(maze.solution_path(), route);
+		maze.run_bfs(false);
+		assert_eq!(maze.solution_path(), route);
+	}
+
+	#[test]
+	fn aldous_broder_opens_every_room()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 31, height: 21 }, GenMethod::AldousBroder).unwrap();
+
+		for position in 0..maze.cells.len()
+		{
+			if maze.is_room(position)
+			{
+				assert_eq!(maze.cells[position].celltype, MazeCellType::Passage);
+			}
+		}
+		// all the rooms and the walls opened between them form a spanning tree
+		assert_eq!(maze.count_passages(), 15 * 10 + 15 * 10 - 1);
+	}
+
+	#[test]
+	fn aldous_broder_with_given_rng()
+	{
+		let mut maze = Maze::with_dimensions(Dimensions { width: 21, height: 15 });
+		maze.generate_aldous_broder(&mut StdRng::seed_from_u64(3)).unwrap();
+		assert_eq!(maze.count_passages(), 10 * 7 + 10 * 7 - 1);
+		assert!(maze.is_solvable());
+
+		let mut again = Maze::with_dimensions(Dimensions { width: 21, height: 15 });
+		again.generate_aldous_broder(&mut StdRng::seed_from_u64(3)).unwrap();
+		assert_eq!(again.to_string(), maze.to_string());
+	}
+
+	#[test]
+	fn aldous_broder_rejects_large_maze()
+	{
+		let mut maze = Maze::new();
+		let size = ALDOUS_BRODER_DIMENSION_MAX + 2;
+		assert!(maze.generate(Dimensions { width: size, height: 11 }, GenMethod::AldousBroder).is_err());
+	}
+
+	#[test]
+	fn progress_is_reported_once_per_percent()
+	{
+		let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+		let reports_clone = reports.clone();
+		let mut maze = Maze::new();
+		maze.set_progress_callback(Some(Arc::new(move |percent| reports_clone.lock().unwrap().push(percent))));
+
+		maze.generate(Dimensions { width: 101, height: 101 }, GenMethod::Prim).unwrap();
+
+		let reports = reports.lock().unwrap();
+		assert!(reports.len() > 10 && reports.len() <= 101);
+		assert!(reports.windows(2).all(|w| w[0] < w[1]));
+		assert_eq!(reports.last(), Some(&100.0));
+	}
+
+	#[test]
+	fn cancelled_generation_fails()
+	{
+		let cancel = Arc::new(AtomicBool::new(true));
+		let mut maze = Maze::new();
+		maze.set_cancel_flag(Some(cancel.clone()));
+
+		for method in [GenMethod::Backtracker, GenMethod::Prim, GenMethod::Kruskal, GenMethod::AldousBroder]
+		{
+			assert!(maze.generate(Dimensions { width: 21, height: 21 }, method).is_err());
+		}
+
+		cancel.store(false, AtomicOrdering::SeqCst);
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Prim).unwrap();
+		cancel.store(true, AtomicOrdering::SeqCst);
+		assert!(maze.run_bfs(false));
+		assert!(maze.solution_path().is_empty());
+	}
+
+	#[test]
+	fn maze_equals_its_clone()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 11, height: 11 }, GenMethod::Backtracker).unwrap();
+		let mut other = maze.clone();
+		assert_eq!(maze, other);
+
+		other.run_bfs(false);
+		assert_eq!(maze, other);
+
+		let position = other.cells.iter().position(|c| c.celltype == MazeCellType::Passage).unwrap();
+		other.cells[position].celltype = MazeCellType::Wall;
+		assert_ne!(maze, other);
+	}
+
+	#[test]
+	fn create_maze_from_dimensions()
+	{
+		let maze = Maze::from_dimensions(Dimensions { width: 21, height: 11 }).unwrap();
+		assert_eq!(maze.dimensions, Dimensions { width: 21, height: 11 });
+		assert_eq!(maze.cells.len(), 21 * 11);
+		assert!(maze.cells.iter().all(|c| c.celltype == MazeCellType::Wall));
+	}
+
+	#[test]
+	fn invalid_dimensions_are_rejected()
+	{
+		assert!(Maze::from_dimensions(Dimensions { width: 9, height: 11 }).is_err());
+		assert!(Maze::from_dimensions(Dimensions { width: 11, height: MAZE_DIMENSION_MAX + 1 }).is_err());
+		assert!(Maze::from_dimensions(Dimensions { width: 12, height: 11 }).is_err());
+	}
+
+	#[test]
+	fn route_cells_match_solution_path()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker).unwrap();
+		assert_eq!(maze.route_cells().count(), 0);
+
+		maze.run_bfs(false);
+		let path = maze.solution_path();
+		let route: Vec<(usize, usize)> = maze.route_cells().map(|(x, y, _)| (x, y)).collect();
+		assert_eq!(route.len(), path.iter().filter(|(x, y)| maze.get(*x, *y).unwrap().on_route).count());
+		assert!(route.iter().all(|p| path.contains(p)));
+	}
+
+	#[test]
+	fn write_and_read_maze_text()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 11 }, GenMethod::Prim).unwrap();
+		maze.run_bfs(false);
+
+		let mut output: Vec<u8> = Vec::new();
+		maze.write_maze(&mut output).unwrap();
+		let text = String::from_utf8(output).unwrap();
+		assert!(text.starts_with("Maze 21 11\n"));
+
+		let mut loaded = Maze::new();
+		loaded.read_maze(text.as_bytes()).unwrap();
+		assert_eq!(loaded, maze);
+	}
+
+	#[test]
+	fn start_and_end_are_in_different_columns()
+	{
+		let mut maze = Maze::new();
+		for seed in 0..200
+		{
+			maze.set_seed(seed);
+			maze.generate(Dimensions { width: 11, height: 11 }, GenMethod::Kruskal).unwrap();
+			assert_ne!(maze.start % 11, maze.end % 11, "seed {}", seed);
+		}
+	}
+
+	#[test]
+	fn path_length_is_the_same_for_bfs_and_a_star()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 41, height: 31 }, GenMethod::Backtracker).unwrap();
+		assert_eq!(maze.path_length(), None);
+
+		assert_eq!(maze.solve(SolveMethod::Bfs, None), SolveResult::Solved);
+		let bfs_length = maze.path_length().unwrap();
+		assert_eq!(bfs_length, maze.solution_path().len() - 1);
+
+		assert_eq!(maze.solve(SolveMethod::AStar, None), SolveResult::Solved);
+		assert_eq!(maze.path_length(), Some(bfs_length));
+	}
+
+	#[test]
+	fn create_maze_from_char_grid()
+	{
+		let rows = [
+			"█S█████████",
+			"█         █",
+			"█████████ █",
+			"█         █",
+			"█ █████████",
+			"█         █",
+			"█████████ █",
+			"█         █",
+			"█ █████████",
+			"█         █",
+			"█████████E█",
+		];
+		let lines: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+
+		let maze = Maze::from_char_grid(&lines).unwrap();
+		assert_eq!(maze.dimensions, Dimensions { width: 11, height: 11 });
+		assert_eq!(maze.start, 1);
+		assert_eq!(maze.end, 119);
+		assert_eq!(maze.cells[12].celltype, MazeCellType::Passage);
+		assert_eq!(maze.cells[22].celltype, MazeCellType::Wall);
+		assert_eq!(maze.to_string().lines().collect::<Vec<&str>>(), rows);
+		assert!(maze.is_solvable());
+	}
+
+	#[test]
+	fn create_maze_from_ascii()
+	{
+		let maze = Maze::from_ascii("
+###S#######
+#   #     #
+# ### ### #
+#     #   #
+# ##### ###
+#   #     #
+### # ### #
+#   #   # #
+# ##### # #
+#       # #
+#########E#
+").unwrap();
+
+		assert_eq!(maze.dimensions, Dimensions { width: 11, height: 11 });
+		assert_eq!(maze.start, 3);
+		assert_eq!(maze.end, 119);
+		assert_eq!(maze.cells[12].celltype, MazeCellType::Passage);
+		assert_eq!(maze.cells[15].celltype, MazeCellType::Wall);
+		assert!(maze.is_solvable());
+
+		assert!(Maze::from_ascii("#S#\n#x#\n#E#").is_err());
+	}
 
-					if let Some(_old) = CLOSED_LIST.iter().find(|x| (x.position == s.position) && (x.f < s.f))
-					{
-						// skip, there is already a shorter way to get there
-						continue;
-					}
+	#[test]
+	fn simplified_graph_has_an_edge_per_corridor()
+	{
+		let mut maze = Maze::from_ascii("
+#S#########
+# #       #
+# # ##### #
+#   #   # #
+##### # # #
+#     #   #
+# #########
+#         #
+######### #
+#         #
+###E#######
+").unwrap();
+		assert!(maze.graph_edges().is_empty());
 
-					OPEN_LIST.push(s).unwrap();
-				}
+		// one intersection above the end, with a short dead end beside it,
+		// and corners everywhere else
+		maze.create_topology_graph();
+		assert_eq!(maze.graph_nodes(), vec![1, 100, 102, 113]);
 
-				if step == true
-				{
-					break
-				}
-			}
+		let edges = maze.graph_edges();
+		assert_eq!(edges.len(), 3);
+		assert!(edges.contains(&(1, 102, 47)));
+		assert!(edges.contains(&(100, 102, 2)));
+		assert!(edges.contains(&(102, 113, 1)));
 
-			// if finished, mark the route (quick'n'dirty)
-			if OPEN_LIST.len() == 0
-			{
-				let mut parent = CLOSED_LIST.last().unwrap().position;
-				let mut found = true;
-				while found && (parent != 0)
-				{
-					found = false;
-					for item in CLOSED_LIST.iter().rev()
-					{
-						if item.position == parent
-						{
-							self.cells[item.position].on_route = true;
-							parent = item.parent;
-							found = true;
-							break;
-						}
-					}
-				}
+		assert_eq!(maze.solve(SolveMethod::Bfs, None), SolveResult::Solved);
+		assert_eq!(maze.path_length(), Some(47 + 1));
+	}
 
-				finished = true;
-			}
-		}
+	#[test]
+	fn ragged_char_grid_is_rejected()
+	{
+		let mut lines: Vec<String> = vec!["█".repeat(11); 11];
+		lines[3] = "█".repeat(10);
 
-		finished
+		assert!(Maze::from_char_grid(&lines).is_err());
+		assert!(Maze::from_char_grid(&[]).is_err());
 	}
 
-	pub fn run_graph_elimination(&mut self, step: bool) -> bool
+	#[test]
+	fn passages_iter_mut_skips_walls()
 	{
-		for i in self.dimensions.width..(self.cells.len() - self.dimensions.width)
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 15, height: 9 }, GenMethod::Kruskal).unwrap();
+		let passages = maze.cells.iter().filter(|cell| cell.celltype != MazeCellType::Wall).count();
+
+		let mut changed = 0;
+		for cell in maze.passages_iter_mut()
 		{
-			// just for optimization, skip walls, start and end
-			if self.cells[i].celltype != MazeCellType::Passage
+			cell.weight = 5;
+			changed += 1;
+		}
+
+		assert_eq!(changed, passages);
+		for cell in maze.cells.iter()
+		{
+			if cell.celltype == MazeCellType::Wall
 			{
-				continue;
+				assert_eq!(cell.weight, DEFAULT_WEIGHT);
 			}
-
-			let mut leaf = Some(i);
-			while let Some(node) = leaf
+			else
 			{
-				if self.get_num_of_graph_connections(node) == 1
-				{
-					leaf = self.remove_dead_end(node);
-
-					if step
-					{
-						info!("Graph elimination stepped");
-						return true;
-					}
-				}
-				else
-				{
-					leaf = None
-				}
+				assert_eq!(cell.weight, 5);
 			}
 		}
+		assert_eq!(maze.cells[maze.start].weight, 5);
+		assert_eq!(maze.cells[maze.end].weight, 5);
+	}
 
-		info!("Graph elimination done");
-		return false;
+	#[test]
+	fn manhattan_distance_on_grid()
+	{
+		let mut maze = Maze::new();
+		maze.reset(Dimensions { width: 7, height: 5 }).unwrap();
+
+		assert_eq!(maze.convert_position_to_coordinates(0), (0, 0));
+		assert_eq!(maze.convert_position_to_coordinates(9), (2, 1));
+		assert_eq!(maze.convert_position_to_coordinates(34), (6, 4));
+
+		assert_eq!(maze.manhattan_distance(0, 0), 0);
+		// same row
+		assert_eq!(maze.manhattan_distance(1, 5), 4);
+		// same column
+		assert_eq!(maze.manhattan_distance(3, 31), 4);
+		// (6, 0) and (0, 1) are next to each other in memory, but far apart
+		assert_eq!(maze.manhattan_distance(6, 7), 7);
+		// (1, 3) and (5, 1)
+		assert_eq!(maze.manhattan_distance(22, 12), 6);
+		assert_eq!(maze.manhattan_distance(12, 22), 6);
+		// opposite corners
+		assert_eq!(maze.manhattan_distance(0, 34), 10);
 	}
 
-	fn get_num_of_graph_connections(&mut self, position: usize) -> usize
+	#[test]
+	fn display_maze_shows_one_line_per_row()
 	{
-		let mut count = 0;
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 11 }, GenMethod::Backtracker).unwrap();
 
-		for i in 0..NUM_OF_DIRECTIONS
+		let text = maze.to_string();
+		assert_eq!(text.lines().count(), maze.dimensions.height);
+		assert!(text.lines().all(|line| line.chars().count() == maze.dimensions.width));
+		assert_eq!(text.matches('S').count(), 1);
+		assert_eq!(text.matches('E').count(), 1);
+	}
+
+	#[test]
+	fn wall_follower_finds_the_end()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 31, height: 31 }, GenMethod::Backtracker).unwrap();
+		assert!(maze.run_wall_follower(false));
+
+		// a maze without loops has only one route, so it is also the shortest
+		let route = maze.solution_path();
+		let mut bfs = maze.clone();
+		bfs.clear_solution();
+		bfs.run_bfs(false);
+		assert_eq!(route, bfs.solution_path());
+		assert!(!route.is_empty());
+
+		let mut steps = 0;
+		maze.clear_solution();
+		while !maze.run_wall_follower(true)
 		{
-			if self.cells[position].nodes[i] != None
-			{
-				count += 1;
-			}
+			steps += 1;
 		}
+		assert!(steps > 0);
+		assert_eq!(maze.solution_path(), route);
+	}
 
-		count
+	#[test]
+	fn turning_directions()
+	{
+		assert_eq!(Direction::North.turn_right(), Direction::East);
+		assert_eq!(Direction::West.turn_right(), Direction::North);
+		assert_eq!(Direction::North.turn_left(), Direction::West);
+		assert_eq!(Direction::South.turn_left(), Direction::East);
 	}
 
-	fn remove_dead_end(&mut self, position: usize) -> Option<usize>
+	#[test]
+	fn isolated_passage_is_a_separate_region()
 	{
-		for i in 0..NUM_OF_DIRECTIONS
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Prim).unwrap();
+		assert_eq!(maze.region_count(), 1);
+
+		// wall in a dead end room, leaving it as a pocket of its own
+		let dead_end = maze.dead_ends().into_iter()
+			.find(|&p| p != maze.start && p != maze.end)
+			.unwrap();
+		for p in maze.get_neighbours(dead_end)
 		{
-			if self.cells[position].nodes[i] != None
-			{
-				if let Some(prev) = self.cells[position].nodes[i]
-				{
-					let opposite = Direction::from_usize(i).get_opposite_direction();
-					self.cells[prev].nodes[opposite as usize] = None;
-					self.cells[position].nodes[i] = None;
-					return Some(prev);
-				}
-			}
+			maze.cells[p].celltype = MazeCellType::Wall;
 		}
-		None
+		assert_eq!(maze.region_count(), 2);
+		assert!(maze.stats_summary().ends_with("regions: 2"));
 	}
 
-	/// Generate a topology graph of this maze.
-	pub fn create_topology_graph(&mut self)
+	#[test]
+	fn generated_mazes_are_verified()
 	{
-		let mut stack: Vec<(usize, usize, Direction)> = Vec::new();
-
-		// add start position to the stack (only way from the start is south)
-		stack.push((self.start, self.start, Direction::South));
-
-		while let Some((previous, position, direction)) = stack.pop()
+		let mut maze = Maze::new();
+		for method in [GenMethod::Backtracker, GenMethod::Prim, GenMethod::Kruskal, GenMethod::AldousBroder]
 		{
-			let node_info = self.check_passage(position, direction);
-			match node_info.nodetype
+			for dimensions in [Dimensions { width: 10, height: 10 }, Dimensions { width: 31, height: 18 }]
 			{
-				GraphNodeType::Straight => {
-					stack.push((previous, node_info.position, direction));
-				},
-				GraphNodeType::Intersection => {
-					for dir in node_info.directions.iter()
-					{
-						stack.push((node_info.position, node_info.position, *dir));
-					}
-					self.add_topology_node(previous, node_info.position, direction);
-				},
-				GraphNodeType::DeadEnd => {
-					self.add_topology_node(previous, node_info.position, direction);
-				},
-				GraphNodeType::End => {
-					self.add_topology_node(previous, node_info.position, direction);
-					//break;
-				},
-				GraphNodeType::NA => {
-					debug!("Internal error. Invalid maze position encountered {}", position);
-					break;
-				},
+				maze.generate(dimensions, method).unwrap();
+				assert!(maze.verify().is_ok(), "{:?} {}", method, dimensions);
 			}
 		}
-
-		self.graph_created = true;
 	}
 
-	fn check_passage(&self, position: usize, direction: Direction) -> GraphNodeInfo
+	#[test]
+	fn stepped_generation_equals_generate()
 	{
-		let mut node_info = GraphNodeInfo {
-			position: 0,
-			nodetype: GraphNodeType::NA,
-			directions: Vec::new()
-		};
-
-		if let Ok(pos) = self.get_neighboring_position(position, direction)
+		let dimensions = Dimensions { width: 25, height: 19 };
+		for method in [GenMethod::Backtracker, GenMethod::Prim, GenMethod::Kruskal, GenMethod::AldousBroder]
 		{
-			if self.cells[pos].celltype == MazeCellType::Passage
-			{
-				let opposite_direction = direction.get_opposite_direction();
-				node_info.directions = self.get_possible_directions(pos, opposite_direction);
+			let mut generated = Maze::new();
+			generated.set_seed(11);
+			generated.generate(dimensions, method).unwrap();
+			assert!(!generated.is_generating());
 
-				match node_info.directions.len()
-				{
-					0 => {
-						node_info.nodetype = GraphNodeType::DeadEnd;
-					},
-					1 => {
-						if node_info.directions[0] == direction
-						{
-							node_info.nodetype = GraphNodeType::Straight;
-						}
-						else
-						{
-							// a corner
-							node_info.nodetype = GraphNodeType::Intersection;
-						}
-					},
-					_ => {
-						node_info.nodetype = GraphNodeType::Intersection;
-					},
-				}
-				node_info.position = pos;
-			}
-			else if self.cells[pos].celltype == MazeCellType::End
+			let mut stepped = Maze::new();
+			stepped.set_seed(11);
+			stepped.start_generation(dimensions, method).unwrap();
+			let mut steps = 0;
+			while !stepped.generate_step().unwrap()
 			{
-				node_info.position = pos;
-				node_info.nodetype = GraphNodeType::End;
+				steps += 1;
+				assert!(stepped.is_generating());
+				assert!(stepped.count_passages() <= generated.count_passages());
 			}
+
+			// every step connects one more room to the maze
+			assert_eq!(steps, stepped.room_count() - 1, "{:?}", method);
+			assert!(!stepped.is_generating());
+			assert_eq!(stepped, generated, "{:?}", method);
+			assert_eq!(stepped.to_string(), generated.to_string());
+			assert!(stepped.generate_step().unwrap());
 		}
-		debug!("Topology: node_info position: {}, nodetype: {}, num directions: {}",
-		       node_info.position,
-		       node_info.nodetype as usize,
-		       node_info.directions.len());
-		return node_info;
 	}
 
-	// Get all possible directions to proceed
-	// (not including the direction given as parameter)
-	fn get_possible_directions(&self, position: usize, direction: Direction) -> Vec<Direction>
+	#[test]
+	fn broken_mazes_fail_verification()
 	{
-		let mut directions: Vec<Direction> = Direction::get_directions().iter().cloned().collect();
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker).unwrap();
+		let width = maze.dimensions.width;
+		let error = |maze: &Maze| maze.verify().unwrap_err().to_string();
 
-		// remove incoming direction from directions
-		if !Direction::remove_direction(&mut directions, direction)
+		let mut broken = maze.clone();
+		broken.cells[broken.end - 2].celltype = MazeCellType::End;
+		assert!(error(&broken).contains("one end"));
+
+		let mut broken = maze.clone();
+		broken.cells[broken.start].celltype = MazeCellType::Passage;
+		assert!(error(&broken).contains("one start"));
+
+		let mut broken = maze.clone();
+		broken.cells[5 * width].celltype = MazeCellType::Passage;
+		assert!(error(&broken).contains("(0, 5) isn't a wall"));
+
+		let mut broken = maze.clone();
+		for cell in broken.cells.iter_mut().filter(|cell| cell.celltype == MazeCellType::Passage).skip(30)
 		{
-			debug!("Internal error. Removing incoming direction failed.");
+			cell.celltype = MazeCellType::Wall;
 		}
+		assert!(error(&broken).contains("30 passages"));
 
-		let mut result = directions.clone();
+		let mut broken = maze.clone();
+		broken.cells[broken.start + width].celltype = MazeCellType::Wall;
+		assert!(error(&broken).contains("no route"));
 
-		// check other directions
-		for test_direction in directions
+		let mut broken = maze.clone();
+		broken.cells.pop();
+		assert!(error(&broken).contains("doesn't match its size"));
+	}
+
+	#[test]
+	fn scaled_maze_keeps_its_route()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 15 }, GenMethod::Backtracker).unwrap();
+
+		let scaled = maze.scale(2).unwrap();
+		assert_eq!(scaled.dimensions, Dimensions { width: 42, height: 30 });
+		assert_eq!(scaled.cells.len(), 4 * maze.cells.len());
+		assert_eq!(scaled.cells.iter().filter(|c| c.celltype == MazeCellType::Start).count(), 1);
+		assert_eq!(scaled.cells.iter().filter(|c| c.celltype == MazeCellType::End).count(), 1);
+		assert_eq!(scaled.count_passages() + 2, 4 * (maze.count_passages() + 2));
+		assert!(scaled.is_solvable());
+
+		assert_eq!(maze.scale(1).unwrap(), maze);
+		assert!(maze.scale(0).is_err());
+		assert!(maze.scale(usize::MAX).is_err());
+	}
+
+	#[test]
+	fn transposed_and_mirrored_mazes_are_solvable()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 15 }, GenMethod::Prim).unwrap();
+		maze.create_topology_graph();
+		maze.cells[maze.start + 21].weight = 4;
+
+		let transposed = maze.transpose();
+		assert_eq!(transposed.dimensions, Dimensions { width: 15, height: 21 });
+		assert_eq!(transposed.start, (maze.start % 21) * 15);
+		assert_eq!(transposed.cells[transposed.start + 1].weight, 4);
+		assert!(!transposed.graph_created);
+		assert!(transposed.cells.iter().all(|cell| cell.nodes == [None; NUM_OF_DIRECTIONS]));
+		assert!(transposed.is_solvable());
+		assert_eq!(transposed.transpose(), maze);
+
+		let mirrored = maze.mirror_horizontal();
+		assert_eq!(mirrored.start, 20 - maze.start);
+		assert!(mirrored.is_solvable());
+		assert_eq!(mirrored.mirror_horizontal(), maze);
+
+		let flipped = maze.mirror_vertical();
+		assert_eq!(flipped.end, maze.end % 21);
+		assert_eq!(flipped.count_passages(), maze.count_passages());
+		assert_eq!(flipped.mirror_vertical(), maze);
+
+		for copy in [transposed, mirrored, flipped]
 		{
-			if let Ok(pos) = self.get_neighboring_position(position, test_direction)
-			{
-				if self.cells[pos].celltype == MazeCellType::Wall
-				{
-					Direction::remove_direction(&mut result, test_direction);
-				}
-			}
-			else
+			for method in [SolveMethod::GraphOnly, SolveMethod::GraphElimination, SolveMethod::AStar,
+			               SolveMethod::Bfs, SolveMethod::Dijkstra, SolveMethod::WallFollower]
 			{
-				Direction::remove_direction(&mut result, test_direction);
+				let mut solved = copy.clone();
+				assert_eq!(solved.solve(method, None), SolveResult::Solved, "{}", method);
 			}
 		}
-
-		result
 	}
 
-	fn add_topology_node(&mut self, start: usize, end: usize, direction: Direction)
+	#[test]
+	fn direction_offsets()
 	{
-		debug!("Topology: adding node, start: {}, end: {}, direction: {}", start, end, direction);
-		self.cells[start].nodes[direction as usize] = Some(end);
-		self.cells[end].nodes[direction.get_opposite_direction() as usize] = Some(start);
+		assert_eq!(Direction::North.to_offset(), (-1, 0));
+		assert_eq!(Direction::East.to_offset(), (0, 1));
+		assert_eq!(Direction::West.to_offset(), (0, -1));
+		assert_eq!(Direction::South.to_offset(), (1, 0));
+
+		let maze = Maze::new();
+		let width = maze.dimensions.width;
+		let center = width + 1;
+		assert_eq!(maze.get_neighboring_position(center, Direction::North).unwrap(), 1);
+		assert_eq!(maze.get_neighboring_position(center, Direction::East).unwrap(), center + 1);
+		assert_eq!(maze.get_neighboring_position(center, Direction::West).unwrap(), width);
+		assert_eq!(maze.get_neighboring_position(center, Direction::South).unwrap(), center + width);
+		assert!(maze.get_neighboring_position(0, Direction::North).is_err());
+		assert!(maze.get_neighboring_position(0, Direction::West).is_err());
+		assert!(maze.get_neighboring_position(width - 1, Direction::East).is_err());
+		assert!(maze.get_neighboring_position(maze.cells.len() - 1, Direction::South).is_err());
 	}
-}
 
-impl<'a> IntoIterator for &'a Maze {
-	type Item = (usize, usize, usize, usize, &'a MazeCell);
-	type IntoIter = MazeGraphIterator<'a>;
+	#[test]
+	fn oversized_maze_is_rejected()
+	{
+		let huge = Dimensions { width: MAZE_DIMENSION_MAX - 1, height: MAZE_DIMENSION_MAX - 1 };
+		assert!(Maze::from_dimensions(huge).is_err());
 
-	fn into_iter(self) -> Self::IntoIter {
-		let mut iter = MazeGraphIterator {
-			maze: self,
-			stack: Vec::new(),
-		};
+		let mut maze = Maze::new();
+		assert!(maze.generate(huge, GenMethod::Backtracker).is_err());
+		assert!(maze.reset(Dimensions { width: usize::MAX, height: 3 }).is_err());
 
-		// find start position
-		for i in 0..self.dimensions.width
+		maze.set_max_cells(20 * 20);
+		assert!(maze.generate(Dimensions { width: 21, height: 21 }, GenMethod::Prim).is_err());
+		maze.generate(Dimensions { width: 19, height: 19 }, GenMethod::Prim).unwrap();
+	}
+
+	#[test]
+	fn json_with_huge_dimensions_is_rejected()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 11, height: 11 }, GenMethod::Backtracker).unwrap();
+		let json = maze.to_json().unwrap();
+		let dimensions = "\"dimensions\":{\"width\":11,\"height\":11}";
+		assert!(json.contains(dimensions));
+
+		let mut loaded = Maze::new();
+		let sizes = [(usize::MAX, usize::MAX),
+		             (usize::MAX / 2, 3),
+		             (MAZE_DIMENSION_MAX, MAZE_DIMENSION_MAX),
+		             (11, 13)];
+		for (width, height) in sizes
 		{
-			if self.cells[i].celltype == MazeCellType::Start
-			{
-				iter.stack.push((i, Direction::South)); // only way from the start is south
-				break;
-			}
+			let changed = format!("\"dimensions\":{{\"width\":{},\"height\":{}}}", width, height);
+			assert!(loaded.from_json(&json.replace(dimensions, &changed)).is_err());
 		}
+		assert_eq!(loaded, Maze::new());
 
-		iter
+		loaded.from_json(&json).unwrap();
+		assert_eq!(loaded, maze);
 	}
-}
 
-pub struct MazeGraphIterator<'a>
-{
-	maze: &'a Maze,
-	stack: Vec<(usize, Direction)>,
-}
+	#[test]
+	fn parallel_distance_field_matches_serial()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 101, height: 101 }, GenMethod::Kruskal).unwrap();
+		maze.braid_randomly(0.5);
+		let mut parallel = maze.clone();
 
-impl<'a> Iterator for MazeGraphIterator<'a>
-{
-	type Item = (usize, usize, usize, usize, &'a MazeCell);
-	fn next(&mut self) -> Option<(usize, usize, usize, usize, &'a MazeCell)>
+		maze.compute_distance_field();
+		parallel.compute_distance_field_parallel();
+
+		assert!(maze.cells.iter().zip(parallel.cells.iter()).all(|(a, b)| a.distance == b.distance));
+	}
+
+	#[test]
+	fn maze_cell_is_small()
 	{
-		let mut new_position = 0;
-		if let Some((position, direction)) = self.stack.pop()
+		assert!(std::mem::size_of::<MazeCell>() <= 48);
+	}
+
+	#[test]
+	fn clear_keeps_dimensions()
+	{
+		let mut maze = Maze::new();
+		maze.generate(Dimensions { width: 21, height: 15 }, GenMethod::Backtracker).unwrap();
+		maze.create_topology_graph();
+		maze.clear();
+
+		assert_eq!(maze.dimensions, Dimensions { width: 21, height: 15 });
+		assert_eq!(maze.cells.len(), 21 * 15);
+		assert!(maze.cells.iter().all(|c| c.celltype == MazeCellType::Wall));
+		assert_eq!(maze.start, 0);
+		assert_eq!(maze.end, 0);
+		assert!(!maze.graph_created);
+	}
+
+	#[test]
+	fn display_dimensions()
+	{
+		assert_eq!(format!("{}", Dimensions { width: 21, height: 15 }), "21x15");
+	}
+
+	#[test]
+	fn generate_and_solve_rectangular_mazes()
+	{
+		for dimensions in [Dimensions { width: 51, height: 11 }, Dimensions { width: 11, height: 51 }]
 		{
-			debug!("Iterator: popped position {}, direction {}", position, direction);
-			if let Some(pos) = self.maze.cells[position].nodes[direction as usize]
+			for method in [GenMethod::Backtracker, GenMethod::Prim, GenMethod::Kruskal, GenMethod::AldousBroder]
 			{
-				new_position = pos;
-				for dir in Direction::get_directions()
+				let mut maze = Maze::new();
+				maze.generate(dimensions, method).unwrap();
+				assert_eq!(maze.dimensions, dimensions);
+				assert!(maze.is_solvable());
+				assert_eq!(maze.start / dimensions.width, 0);
+				assert_eq!(maze.end / dimensions.width, dimensions.height - 1);
+
+				maze.run_bfs(false);
+				let route = maze.solution_path();
+				assert_eq!(route.first(), Some(&(maze.start % dimensions.width, 0)));
+				assert_eq!(route.last(), Some(&(maze.end % dimensions.width, dimensions.height - 1)));
+
+				for solve in [Maze::run_a_star, Maze::run_dijkstra, Maze::run_wall_follower]
 				{
-					if (self.maze.cells[pos].nodes[dir as usize] != None) &&
-					   (dir != direction.get_opposite_direction())
-					{
-						self.stack.push((pos, dir));
-					}
+					let mut solved = maze.clone();
+					solved.clear_solution();
+					assert!(solve(&mut solved, false));
+					assert_eq!(solved.solution_path(), route);
 				}
-			}
-
-			let y = new_position / self.maze.dimensions.width;
-			let x = new_position % self.maze.dimensions.width;
-			let prev_y = position / self.maze.dimensions.width;
-			let prev_x = position % self.maze.dimensions.width;
 
-			return Some((prev_x, prev_y, x, y, &self.maze.cells[position]));
+				let mut graph = maze.clone();
+				graph.clear_solution();
+				graph.create_topology_graph();
+				graph.mark_graph_route();
+				assert_eq!(graph.solution_path(), route);
+			}
 		}
-		None
-    }
+	}
 }
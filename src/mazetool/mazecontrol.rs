@@ -3,16 +3,18 @@
 //! Implements the application logic.
 //! Supports different user interface implementations.
 
-use std::sync::{ Arc, Mutex, MutexGuard };
+use std::cell::RefCell;
+use std::sync::{ Arc, Mutex };
+use std::sync::atomic::{ AtomicBool, Ordering };
 use std::thread;
 use std::result::Result;
 
-use crossbeam::channel::{Receiver, Sender};
-use rand::seq::SliceRandom;
+use crossbeam::channel::{Receiver, Sender, unbounded};
 
 use super::common::{ UIRequest, Job, AppError };
-use super::common::SolveMethod;
-use super::maze::{ Direction, Dimensions, Maze };
+use super::common::{ SolveMethod, GenMethod, DEFAULT_MAX_RETRIES, retry_unless };
+use super::maze::{ Dimensions, Maze, ProgressCallback, SolveResult, NUM_OF_DIRECTIONS };
+use super::export::DEFAULT_CELL_PX;
 
 /// A class for main logic (controller)
 ///
@@ -22,6 +24,15 @@ pub struct MazeControl
 	tx: Sender<UIRequest>,
 	maze: Arc<Mutex<Maze>>,
 	running: bool,
+	max_retries: usize,
+	step_method: SolveMethod,
+	step_finished: bool,
+	braid: f32,
+	hard: bool,
+	interactive: bool,
+	last_error: RefCell<Option<String>>,
+	cancel: Arc<AtomicBool>,
+	pool: Option<rayon::ThreadPool>,
 }
 
 impl MazeControl
@@ -29,15 +40,36 @@ impl MazeControl
 	/// Creates a new MazeControl instance.
 	pub fn new(tx : Sender<UIRequest>) -> Self
 	{
+		let cancel = Arc::new(AtomicBool::new(false));
+		let mut maze = Maze::new();
+		maze.set_progress_callback(Some(MazeControl::progress_callback(tx.clone())));
+		maze.set_cancel_flag(Some(cancel.clone()));
 		let mc = MazeControl
 		{
 			tx: tx,
-			maze: Arc::new(Mutex::new(Maze::new())),
+			maze: Arc::new(Mutex::new(maze)),
 			running: false,
+			max_retries: DEFAULT_MAX_RETRIES,
+			step_method: SolveMethod::AStar,
+			step_finished: false,
+			braid: 0.0,
+			hard: false,
+			interactive: false,
+			last_error: RefCell::new(None),
+			cancel,
+			pool: None,
 		};
 		return mc;
 	}
 
+	/// Create a callback sending the progress of long operations to the UI
+	fn progress_callback(tx: Sender<UIRequest>) -> ProgressCallback
+	{
+		Arc::new(move |percent| {
+			tx.send(UIRequest::ShowProgress(percent)).unwrap_or_else(|_| return);
+		})
+	}
+
 	/// Run the control
 	///
 	/// Initializes and runs the UI (which must create its own thread).
@@ -45,16 +77,42 @@ impl MazeControl
 	///
 	/// Communicates with the UI using channels.
 	///
-	pub fn run(rx: Receiver<Job>, tx : Sender<UIRequest>) -> thread::JoinHandle<()>
+	/// The thread returns the last error of the jobs requested before
+	/// `Job::StartInteractive`, if any. Errors of the jobs requested
+	/// interactively after it are only shown to the user.
+	///
+	/// Jobs are received in a separate thread, so a cancel request
+	/// can interrupt a long running job right away.
+	///
+	pub fn run(rx: Receiver<Job>, tx : Sender<UIRequest>) -> thread::JoinHandle<Option<AppError>>
 	{
 		let thread_tx = tx.clone();
+		let mut mc = MazeControl::new(thread_tx);
+		let cancel = mc.cancel.clone();
+		let (job_tx, job_rx) = unbounded();
+
+		let builder = thread::Builder::new().name("Jobs".to_string());
+		builder.spawn(move || {
+			for job in rx.iter()
+			{
+				let quit = matches!(job, Job::Quit);
+				if let Job::Cancel = job
+				{
+					cancel.store(true, Ordering::SeqCst);
+				}
+				if job_tx.send(job).is_err() || quit
+				{
+					break;
+				}
+			}
+		}).unwrap();
 
 		let builder = thread::Builder::new().name("Control".to_string());
 		let handle: thread::JoinHandle<_> = builder.spawn(move || {
-			let mut mc = MazeControl::new(thread_tx);
 			info!("Starting control thread");
-			mc.run_message_loop(&rx);
+			mc.run_message_loop(&job_rx);
 			info!("Exiting control thread");
+			mc.last_error.take().map(|message| AppError::new(&message))
 		}).unwrap();
 
 		info!("Main thread continues");
@@ -74,20 +132,79 @@ impl MazeControl
 					info!("Control: Received job: {:?}", job);
 					match job
 					{
-						Job::GenerateMaze(dimensions) => {
-							self.tx.send(UIRequest::ShowInfo("Generating...".to_string()))
-								.unwrap_or_else(|_| return);
-							match self.generate_maze(dimensions)
+						Job::GenerateMaze(dimensions, method) => {
+							self.handle_generate(dimensions, method);
+						},
+						Job::GenerateStep(dimensions, method) => {
+							self.generate_step(dimensions, method);
+						},
+						Job::GenerateAndSolve(dimensions, gen_method, solve_method) => {
+							if self.handle_generate(dimensions, gen_method)
+							{
+								self.handle_solve(solve_method);
+							}
+						},
+						Job::LoadMaze(filename) => {
+							match self.load_maze(&filename)
 							{
-								Ok(_) => info!("Maze generated successfully"),
-								Err(e) => self.show_error(format!("Error generating maze: {}", e))
+								Ok(_) => info!("Maze loaded successfully"),
+								Err(e) => self.show_error(format!("Error loading maze: {}", e))
 							};
 						},
+						Job::ValidateMaze => {
+							self.validate_maze();
+						},
+						Job::ShowStats => {
+							self.show_stats();
+						},
 						Job::SolveMaze(method) => {
-							self.solve_maze(method);
+							self.handle_solve(method);
+						},
+						Job::SolveStep => {
+							self.solve_step();
+						},
+						Job::ClearSolution => {
+							self.clear_solution();
+						},
+						Job::SetEndpoints(start, end) => {
+							self.set_endpoints(start, end);
+						},
+						Job::SetMaxRetries(max_retries) => {
+							self.max_retries = max_retries;
+						},
+						Job::SetBraid(ratio) => {
+							self.braid = ratio;
+						},
+						Job::SetHard(hard) => {
+							self.hard = hard;
+						},
+						Job::SetThreads(threads) => {
+							self.set_threads(threads);
+						},
+						Job::SaveMaze(filename) => {
+							self.save_maze(&filename);
+						},
+						Job::ExportPng(filename) => {
+							self.export_png(&filename);
+						},
+						Job::ExportDot(filename) => {
+							self.export_dot(&filename);
+						},
+						Job::StartInteractive => {
+							self.interactive = true;
+						},
+						Job::SetSeed(seed) => {
+							match self.maze.lock()
+							{
+								Ok(mut m) => m.set_seed(seed),
+								Err(e) => self.show_error(e.to_string()),
+							}
+						},
+						Job::Cancel => {
+							self.cancel.store(false, Ordering::SeqCst);
 						},
 						Job::Quit => {
-							break;
+							self.quit();
 						},
 					};
 				},
@@ -103,214 +220,517 @@ impl MazeControl
 	///
 	fn show_error(&self, message: String)
 	{
-		self.tx.send(UIRequest::ShowError(message)).unwrap();
+		// the user can recover from errors in an interactive session
+		if !self.interactive
+		{
+			self.last_error.replace(Some(message.clone()));
+		}
+		self.tx.send(UIRequest::ShowError(message)).unwrap_or_else(|_| return);
+	}
+
+	/// Check if the jobs received so far have been cancelled
+	fn is_cancelled(&self) -> bool
+	{
+		self.cancel.load(Ordering::SeqCst)
 	}
 
-	/// Generate a new maze of the given size
+	/// Generate a maze and tell the UI how it went
 	///
-	/// A simple recursive backtracking algorithm.
+	/// # Parameters
+	///
+	/// * `dimensions`      - Dimensions of the new maze
+	/// * `method`          - Algorithm used to generate the maze
 	///
-	/// 1. Close all cells
-	/// 2. Choose starting cell and open it. This is the current cell
-	/// 3. Pick a cell adjacent to the current cell that hasn’t been visited and open it.
-	///    It becomes the current cell.
-	/// 4. Repeat 2 until no adjacent wall can be selected
-	/// 5. The previous cell becomes the current cell.
-	///    If this cell is the starting cell, then we are done. Else go to 2.
+	/// Returns true if the maze was generated.
+	///
+	fn handle_generate(&mut self, dimensions: Dimensions, method: GenMethod) -> bool
+	{
+		self.tx.send(UIRequest::ShowInfo("Generating...".to_string())).unwrap_or_else(|_| return);
+		match self.generate_maze(dimensions, method)
+		{
+			Ok(_) => {
+				info!("Maze generated successfully");
+				true
+			},
+			Err(_) if self.is_cancelled() => {
+				self.show_cancelled();
+				false
+			},
+			Err(e) => {
+				self.show_error(format!("Error generating maze: {}", e));
+				false
+			},
+		}
+	}
+
+	/// Solve the maze and tell the UI if solving was cancelled
 	///
 	/// # Parameters
 	///
-	/// * `dimensions`  - The dimensions of a new maze to generate
+	/// * `method`          - Method to use to solve the maze
 	///
-	fn generate_maze(&mut self, dimensions: Dimensions) -> Result<(), AppError>
+	fn handle_solve(&mut self, method: SolveMethod)
 	{
-		info!("Request to generate a maze received");
+		self.step_method = method;
+		self.solve_maze(method);
+		if self.is_cancelled()
+		{
+			self.show_cancelled();
+		}
+	}
 
-		match self.maze.lock()
+	/// Send a job to the UI to tell the last job was cancelled
+	fn show_cancelled(&self)
+	{
+		info!("Job cancelled");
+		self.tx.send(UIRequest::ShowInfo("Cancelled".to_string())).unwrap_or_else(|_| return);
+	}
+
+	/// Use a pool of threads for computing distance fields
+	///
+	/// # Parameters
+	///
+	/// * `threads`         - Number of threads, 0 to compute in the control thread
+	///
+	fn set_threads(&mut self, threads: usize)
+	{
+		self.pool = None;
+		if threads == 0
 		{
-			Ok(mut m) => {
-				m.reset(dimensions);
+			return;
+		}
+
+		match rayon::ThreadPoolBuilder::new().num_threads(threads).build()
+		{
+			Ok(pool) => self.pool = Some(pool),
+			Err(e) => self.show_error(format!("Error creating threads: {}", e)),
+		}
+	}
+
+	/// Compute the distance field of a maze, in parallel if threads are set
+	///
+	/// # Parameters
+	///
+	/// * `maze`            - The maze to compute the distances for
+	///
+	fn compute_distance_field(&self, maze: &mut Maze)
+	{
+		match &self.pool
+		{
+			Some(pool) => pool.install(|| maze.compute_distance_field_parallel()),
+			None => maze.compute_distance_field(),
+		}
+	}
+
+	/// Load a maze from a file and show it
+	///
+	/// The current maze is kept if loading fails.
+	///
+	/// # Parameters
+	///
+	/// * `filename`    - Source filename
+	///
+	fn load_maze(&mut self, filename: &str) -> Result<(), AppError>
+	{
+		info!("Request to load a maze received");
+
+		let mut loaded = Maze::new();
+		loaded.read_from_file(filename)?;
+		self.compute_distance_field(&mut loaded);
 
-				// generation could be started from any position, but we choose the start position
-				let position = m.randomize_start_position();
-				debug!("Start position: {}", position);
+		{
+			let mut m = self.maze.lock()?;
+			loaded.set_progress_callback(m.progress_callback());
+			loaded.set_cancel_flag(m.cancel_flag());
+			*m = loaded;
+			self.step_finished = false;
+		}
+
+		self.tx.send(UIRequest::ShowMaze(self.maze.clone())).unwrap_or_else(|_| return);
+		Ok(())
+	}
 
-				self.dig(&mut m, position)?;
-				m.insert_start_and_end_positions();
-				m.write_to_file("saved.maze")?;
-				m.read_from_file("saved.maze")?; //TODO: these are here temporarily, do these some other way
+	/// Move the start and the end of the maze and show it
+	///
+	/// # Parameters
+	///
+	/// * `start`           - Position of the new start cell
+	/// * `end`             - Position of the new end cell
+	///
+	fn set_endpoints(&mut self, start: usize, end: usize)
+	{
+		match self.maze.lock()
+		{
+			Ok(mut m) => {
+				if let Err(e) = m.set_endpoints(start, end)
+				{
+					self.show_error(format!("Error setting start and end: {}", e));
+					return;
+				}
+				self.compute_distance_field(&mut m);
+				self.step_finished = false;
 			},
 			Err(e) => {
 				self.show_error(e.to_string());
+				return;
 			},
 		}
 
 		self.tx.send(UIRequest::ShowMaze(self.maze.clone())).unwrap_or_else(|_| return);
-		//self.tx.send(UIRequest::Quit).unwrap_or_else(|_| return);
-		Ok(())
 	}
 
-	/// Iteratively dig passages in the maze
+	/// Clear the solution of the maze and show it
 	///
-	/// # Parameters
-	/// * `maze`        - The maze data structure
-	/// * `start`       - Start position in the maze
+	/// The maze stays the same, so it can be solved again with another method.
 	///
-	fn dig(&self, maze: &mut MutexGuard<Maze>, start: usize) -> Result<(), AppError>
+	fn clear_solution(&mut self)
 	{
-		let mut positions : Vec<(usize, Direction)> = Vec::new();
+		match self.maze.lock()
+		{
+			Ok(mut m) => {
+				m.clear_solution();
+				self.compute_distance_field(&mut m);
+				self.step_finished = false;
+			},
+			Err(e) => {
+				self.show_error(e.to_string());
+				return;
+			},
+		}
 
-		MazeControl::push_new_position(&mut positions, start);
+		self.tx.send(UIRequest::ShowMaze(self.maze.clone())).unwrap_or_else(|_| return);
+	}
 
-		while let Some((position, direction)) = positions.pop()
+	/// Show statistics of the maze
+	fn show_stats(&self)
+	{
+		match self.maze.lock()
 		{
-			debug!("Moving to position {}", position);
+			Ok(m) => {
+				self.tx.send(UIRequest::ShowInfo(m.stats_summary())).unwrap_or_else(|_| return);
+			},
+			Err(e) => {
+				self.show_error(e.to_string());
+			},
+		}
+	}
 
-			debug!("Checking if digging possible at position {}", position);
-			match maze.is_diggable(position, direction)
-			{
-				Ok(result) => {
-					if result == true
-					{
-						debug!("Digging new passage towards {}", direction);
-						let new_position = maze.dig_passage(position, direction)?;
-						MazeControl::push_new_position(&mut positions, new_position);
-						continue;
-					}
-					else
-					{
-						debug!("Can't dig to {}", direction);
-					}
-				},
-				Err(e) => {
-					debug!("Can't dig to {}, error: {}", direction, e.to_string());
+	/// Check if the maze can be solved and show the result
+	fn validate_maze(&self)
+	{
+		match self.maze.lock()
+		{
+			Ok(m) => {
+				let mut result = "unsolvable";
+				if m.is_solvable()
+				{
+					result = "solvable";
 				}
-			}
-			debug!("Stepping back from {}", position);
+				self.tx.send(UIRequest::ShowInfo(result.to_string())).unwrap_or_else(|_| return);
+			},
+			Err(e) => {
+				self.show_error(e.to_string());
+			},
 		}
-		Ok(())
 	}
 
-	fn push_new_position(positions: &mut Vec<(usize, Direction)>, position: usize)
+	/// Save the maze to a file
+	///
+	/// # Parameters
+	///
+	/// * `filename`    - Target filename
+	///
+	fn save_maze(&self, filename: &str)
 	{
-		let mut rng = rand::thread_rng();
-		let mut directions = Direction::get_directions();
-		directions.shuffle(&mut rng);
+		match self.maze.lock()
+		{
+			Ok(m) => {
+				match m.save(filename)
+				{
+					Ok(_) => info!("Maze saved to {}", filename),
+					Err(e) => self.show_error(format!("Error saving maze: {}", e)),
+				}
+			},
+			Err(e) => {
+				self.show_error(e.to_string());
+			},
+		}
+	}
 
-		for direction in directions.iter()
+	/// Save the maze as a PNG image
+	///
+	/// # Parameters
+	///
+	/// * `filename`    - Target filename
+	///
+	fn export_png(&self, filename: &str)
+	{
+		match self.maze.lock()
 		{
-			positions.push((position, *direction));
+			Ok(m) => {
+				match m.write_to_png(filename, DEFAULT_CELL_PX)
+				{
+					Ok(_) => info!("Maze exported to {}", filename),
+					Err(e) => self.show_error(format!("Error exporting maze: {}", e)),
+				}
+			},
+			Err(e) => {
+				self.show_error(e.to_string());
+			},
 		}
 	}
 
-	fn generate_graph(&mut self) -> Result<(), AppError>
+	/// Save the topology graph of the maze in the Graphviz DOT format
+	///
+	/// # Parameters
+	///
+	/// * `filename`    - Target filename
+	///
+	fn export_dot(&self, filename: &str)
 	{
 		match self.maze.lock()
 		{
 			Ok(mut m) => {
-				debug!("Generating topogoly graph");
-				m.create_topology_graph();
+				match m.write_to_dot(filename)
+				{
+					Ok(_) => info!("Maze graph exported to {}", filename),
+					Err(e) => self.show_error(format!("Error exporting maze graph: {}", e)),
+				}
 			},
 			Err(e) => {
 				self.show_error(e.to_string());
 			},
 		}
-
-		self.tx.send(UIRequest::ShowMaze(self.maze.clone())).unwrap_or_else(|_| return);
-		Ok(())
 	}
 
-	fn run_graph_elimination(&mut self) -> Result<(), AppError>
+	/// Generate a new maze of the given size
+	///
+	/// # Parameters
+	///
+	/// * `dimensions`  - The dimensions of a new maze to generate
+	/// * `method`      - The algorithm used for generating the maze
+	///
+	fn generate_maze(&mut self, dimensions: Dimensions, method: GenMethod) -> Result<(), AppError>
 	{
-		let mut finished = false;
-		let mut delay: u64 = 100; // abit hacky delay to show progress on the ui
+		info!("Request to generate a maze received");
 
-		while !finished
 		{
-			match self.maze.lock()
+			let mut m = self.maze.lock()?;
+			// a cancelled generation stops instead of starting over
+			retry_unless(self.max_retries, "generate a maze", || self.is_cancelled(), |attempt| {
+				debug!("Generating maze, attempt {}", attempt);
+				m.generate(dimensions, method)
+			})?;
+			if self.braid > 0.0
 			{
-				Ok(mut m) => {
-					debug!("Eliminating dead ends from the graph");
-					finished = !m.run_graph_elimination(true);
-					delay = 100 - m.dimensions.width as u64;
-				},
-				Err(e) => {
-					self.show_error(e.to_string());
-				},
+				m.braid_randomly(self.braid);
 			}
-			self.tx.send(UIRequest::ShowMaze(self.maze.clone())).unwrap_or_else(|_| return);
-			std::thread::sleep(std::time::Duration::from_millis(delay));
+			if self.hard
+			{
+				m.place_endpoints_at_extremes()?;
+			}
+			self.step_finished = false;
+			self.compute_distance_field(&mut m);
 		}
+
+		self.tx.send(UIRequest::ShowMaze(self.maze.clone())).unwrap_or_else(|_| return);
+		//self.tx.send(UIRequest::Quit).unwrap_or_else(|_| return);
 		Ok(())
 	}
 
-	fn run_a_star(&mut self) -> Result<(), AppError>
+	/// Carve one passage of a maze being generated and show the result
+	///
+	/// A new maze is started if none is being generated. Braiding and
+	/// the other settings are applied once the maze is finished.
+	///
+	/// # Parameters
+	///
+	/// * `dimensions`  - The dimensions of a new maze to generate
+	/// * `method`      - The algorithm used for generating a new maze
+	///
+	fn generate_step(&mut self, dimensions: Dimensions, method: GenMethod)
 	{
-		let mut finished = false;
-		let mut delay: u64 = 100; // abit hacky delay to show progress on the ui
+		match self.advance_generation(dimensions, method)
+		{
+			Ok(true) => info!("Maze generated successfully"),
+			Ok(false) => {},
+			Err(_) if self.is_cancelled() => {
+				self.show_cancelled();
+				return;
+			},
+			Err(e) => {
+				self.show_error(format!("Error generating maze: {}", e));
+				return;
+			},
+		}
+
+		self.tx.send(UIRequest::ShowMaze(self.maze.clone())).unwrap_or_else(|_| return);
+	}
 
-		while !finished
+	/// Carve one passage of the maze, finishing it after the last one
+	///
+	/// Returns true if the maze was finished, or AppError on failure.
+	///
+	fn advance_generation(&mut self, dimensions: Dimensions, method: GenMethod) -> Result<bool, AppError>
+	{
+		let mut m = self.maze.lock()?;
+		if !m.is_generating()
 		{
-			match self.maze.lock()
-			{
-				Ok(mut m) => {
-					finished = m.run_a_star(true);
-					delay = 100 - m.dimensions.width as u64;
-				},
-				Err(e) => {
-					self.show_error(e.to_string());
-				},
-			}
-			self.tx.send(UIRequest::ShowMaze(self.maze.clone())).unwrap_or_else(|_| return);
-			std::thread::sleep(std::time::Duration::from_millis(delay));
+			m.start_generation(dimensions, method)?;
 		}
-		Ok(())
+		if !m.generate_step()?
+		{
+			return Ok(false);
+		}
+
+		if self.braid > 0.0
+		{
+			m.braid_randomly(self.braid);
+		}
+		if self.hard
+		{
+			m.place_endpoints_at_extremes()?;
+		}
+		self.step_finished = false;
+		self.compute_distance_field(&mut m);
+
+		Ok(true)
+	}
+
+	/// Advance solving the maze by one step and show the result
+	///
+	/// Uses the method of the last solve request, A* by default.
+	/// Does nothing once solving has finished.
+	///
+	fn solve_step(&mut self)
+	{
+		if self.step_finished
+		{
+			self.tx.send(UIRequest::ShowInfo("Solving finished".to_string())).unwrap_or_else(|_| return);
+			return;
+		}
+
+		let mut solved = false;
+		match self.maze.lock()
+		{
+			Ok(mut m) => {
+				self.step_finished = match self.step_method
+				{
+					SolveMethod::GraphOnly => {
+						if !m.graph_created
+						{
+							m.create_topology_graph();
+						}
+						m.mark_graph_route()
+					},
+					SolveMethod::GraphElimination => {
+						if !m.graph_created
+						{
+							m.create_topology_graph();
+							false
+						}
+						else if !m.run_graph_elimination(true)
+						{
+							m.mark_graph_route();
+							true
+						}
+						else
+						{
+							false
+						}
+					},
+					SolveMethod::AStar => m.run_a_star(true),
+					SolveMethod::Bfs => m.run_bfs(true),
+					SolveMethod::Dijkstra => m.run_dijkstra(true),
+					SolveMethod::WallFollower => m.run_wall_follower(true),
+				};
+				solved = self.step_finished && m.cells[m.end].on_route;
+			},
+			Err(e) => {
+				self.show_error(e.to_string());
+			},
+		}
+
+		self.show_solving(solved, self.step_method);
+	}
+
+	/// Show the maze being solved
+	///
+	/// # Parameters
+	///
+	/// * `solved`          - Whether a route to the end was found
+	/// * `method`          - Method used to solve the maze
+	///
+	fn show_solving(&self, solved: bool, method: SolveMethod)
+	{
+		let request = UIRequest::ShowMazeWithMeta {
+			maze: self.maze.clone(),
+			solved,
+			method: Some(method),
+		};
+		self.tx.send(request).unwrap_or_else(|_| return);
 	}
 
 	/// Solve an already generated maze.
 	///
-	/// Find a path through the maze.
+	/// Find a path through the maze. The chosen solver is run until it
+	/// finishes or takes more steps than there are directions in all cells
+	/// together, and the solved maze is sent to the UI.
 	///
 	/// # Parameters
 	/// * `method`      - Method to use to solve the maze
 	///
-	fn solve_maze(&mut self, method: SolveMethod)
+	fn solve_maze(&self, method: SolveMethod)
 	{
-		std::thread::sleep(std::time::Duration::from_millis(1000));
-		match method
+		info!("Solving the maze with {}", method);
+		let (result, stats) = match self.maze.lock()
 		{
-			SolveMethod::GraphOnly => {
-				match self.generate_graph()
-				{
-					Ok(_) => info!("Graph generated successfully"),
-					Err(e) => self.show_error(format!("Error generating graph: {}", e))
-				};
+			Ok(mut m) => {
+				let max_steps = NUM_OF_DIRECTIONS * m.cells.len();
+				(m.solve(method, Some(max_steps)), m.solve_stats())
 			},
-			SolveMethod::GraphElimination => {
-				match self.generate_graph()
-				{
-					Ok(_) => info!("Graph generated successfully"),
-					Err(e) => self.show_error(format!("Error generating graph: {}", e))
-				};
-				match self.run_graph_elimination()
-				{
-					Ok(_) => info!("Graph elimination successful"),
-					Err(e) => self.show_error(format!("Error with graph elimination: {}", e))
-				}
+			Err(e) => {
+				self.show_error(e.to_string());
+				return;
 			},
-			SolveMethod::AStar => {
-				match self.run_a_star()
-				{
-					Ok(_) => info!("A* successful"),
-					Err(e) => self.show_error(format!("Error with A*: {}", e))
-				}
-			}
+		};
+
+		self.show_solving(result == SolveResult::Solved, method);
+		if let (SolveResult::Solved, Some(stats)) = (result, stats)
+		{
+			self.tx.send(UIRequest::ShowInfo(format!("{}: {}", method, stats))).unwrap_or_else(|_| return);
+		}
+		match result
+		{
+			SolveResult::Solved => info!("Solving with {} successful", method),
+			_ if self.is_cancelled() => {},
+			SolveResult::NoPath => self.show_error(format!("{} found no route to the end", method)),
+			SolveResult::TimedOut => self.show_error(format!("{} took too many steps to solve the maze", method)),
 		}
 	}
 
-	//fn quit(&mut self)
-	//{
-	//	self.tx.send(UIRequest::Quit).unwrap_or_else(|_| return);
-	//	self.running = false;
-	//}
+	/// Stop the control and tell the UI to quit too
+	fn quit(&mut self)
+	{
+		self.tx.send(UIRequest::Quit).unwrap_or_else(|_| return);
+		self.running = false;
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn cancelled_generation_is_not_retried()
+	{
+		let (tx, _rx) = unbounded();
+		let mut mc = MazeControl::new(tx);
+		mc.max_retries = 5;
+		mc.cancel.store(true, Ordering::SeqCst);
+
+		let error = mc.generate_maze(Dimensions { width: 21, height: 21 }, GenMethod::Backtracker).unwrap_err();
+		assert_eq!(error.to_string(), "Error: Cancelled");
+	}
 }
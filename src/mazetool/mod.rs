@@ -4,3 +4,6 @@ pub mod cli;
 pub mod gui;
 pub mod common;
 pub mod maze;
+pub mod export;
+pub mod benchmark;
+pub mod theme;
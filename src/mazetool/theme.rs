@@ -0,0 +1,116 @@
+// Mazetool - color themes of the graphical interface
+
+use std::str::FromStr;
+
+/// Colors used for drawing a maze, as RGBA values from 0.0 to 1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme
+{
+	pub background: [f32; 4],
+	pub wall: [f32; 4],
+	pub route: [f32; 4],
+	pub visited: [f32; 4],
+	pub start: [f32; 4],
+	pub end: [f32; 4],
+	pub graph: [f32; 4],
+	pub text: [f32; 4],
+}
+
+impl Theme
+{
+	/// White walls on a dark blue background, the original colors
+	pub fn classic() -> Theme
+	{
+		Theme {
+			background: [0.1, 0.2, 0.3, 1.0],
+			wall: [1.0, 1.0, 1.0, 1.0],
+			route: [0.0, 1.0, 0.0, 1.0],
+			visited: [0.0, 0.5, 0.5, 1.0],
+			start: [0.0, 0.6, 0.0, 1.0],
+			end: [1.0, 0.0, 0.0, 1.0],
+			graph: [0.0, 1.0, 0.0, 1.0],
+			text: [1.0, 1.0, 0.0, 1.0],
+		}
+	}
+
+	/// Dim gray walls on black, easier on the eyes in a dark room
+	pub fn dark() -> Theme
+	{
+		Theme {
+			background: [0.05, 0.05, 0.05, 1.0],
+			wall: [0.35, 0.35, 0.35, 1.0],
+			route: [1.0, 0.6, 0.0, 1.0],
+			visited: [0.2, 0.2, 0.4, 1.0],
+			start: [0.0, 0.8, 0.4, 1.0],
+			end: [0.9, 0.2, 0.2, 1.0],
+			graph: [1.0, 0.6, 0.0, 1.0],
+			text: [0.9, 0.9, 0.9, 1.0],
+		}
+	}
+
+	/// Black and white with a yellow route and blue visited cells
+	///
+	/// The route and the visited cells differ in brightness as well as
+	/// in hue, so they can be told apart without seeing red and green.
+	pub fn high_contrast() -> Theme
+	{
+		Theme {
+			background: [0.0, 0.0, 0.0, 1.0],
+			wall: [1.0, 1.0, 1.0, 1.0],
+			route: [1.0, 1.0, 0.0, 1.0],
+			visited: [0.0, 0.2, 0.8, 1.0],
+			start: [0.0, 1.0, 1.0, 1.0],
+			end: [1.0, 0.0, 1.0, 1.0],
+			graph: [1.0, 1.0, 0.0, 1.0],
+			text: [1.0, 1.0, 1.0, 1.0],
+		}
+	}
+}
+
+impl Default for Theme
+{
+	fn default() -> Theme
+	{
+		Theme::classic()
+	}
+}
+
+impl FromStr for Theme
+{
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Theme, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "classic"           => Ok(Theme::classic()),
+            "dark"              => Ok(Theme::dark()),
+            "highcontrast"      => Ok(Theme::high_contrast()),
+            _                   => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn built_in_themes()
+	{
+		let themes = [Theme::classic(), Theme::dark(), Theme::high_contrast()];
+		for theme in themes.iter()
+		{
+			let colors = [theme.background, theme.wall, theme.route, theme.visited,
+			              theme.start, theme.end, theme.graph, theme.text];
+			assert!(colors.iter().flatten().all(|&c| (0.0..=1.0).contains(&c)));
+			assert_ne!(theme.route, theme.visited);
+			assert_ne!(theme.wall, theme.background);
+		}
+
+		assert_eq!(Theme::from_str("classic"), Ok(Theme::classic()));
+		assert_eq!(Theme::from_str("Dark"), Ok(Theme::dark()));
+		assert_eq!(Theme::from_str("highcontrast"), Ok(Theme::high_contrast()));
+		assert_eq!(Theme::from_str("rainbow"), Err(()));
+		assert_eq!(Theme::default(), Theme::classic());
+	}
+}
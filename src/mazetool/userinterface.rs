@@ -5,6 +5,63 @@ use super::common::{ Job, UIRequest };
 /// Trait for features required from a Mazetool user interface
 pub trait UserInterface
 {
+	/// Create a new user interface communicating with the control through the given channels
 	fn new(tx: Sender<Job>, rx: Receiver<UIRequest>) -> Self;
+
+	/// Run the user interface until it is closed
+	///
+	/// # Parameters
+	///
+	/// * `show_distances`  - Show the calculated distances of the maze cells
+	///
 	fn run(&mut self, show_distances: bool);
 }
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crossbeam::channel::unbounded;
+
+	struct DummyInterface
+	{
+		rx: Receiver<UIRequest>,
+		show_distances: bool,
+	}
+
+	impl UserInterface for DummyInterface
+	{
+		fn new(_tx: Sender<Job>, rx: Receiver<UIRequest>) -> Self
+		{
+			DummyInterface
+			{
+				rx,
+				show_distances: false,
+			}
+		}
+
+		fn run(&mut self, show_distances: bool)
+		{
+			self.show_distances = show_distances;
+			while let Ok(request) = self.rx.recv()
+			{
+				if let UIRequest::Quit = request
+				{
+					break;
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn run_dummy_interface()
+	{
+		let (from_ui_tx, _from_ui_rx) = unbounded();
+		let (to_ui_tx, to_ui_rx) = unbounded();
+		let mut ui = DummyInterface::new(from_ui_tx, to_ui_rx);
+
+		to_ui_tx.send(UIRequest::Quit).unwrap();
+		ui.run(true);
+		assert!(ui.show_distances);
+	}
+}
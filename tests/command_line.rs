@@ -0,0 +1,79 @@
+// Tests running the mazetool binary from the command line
+
+use std::process::{ Command, Output, Stdio };
+use std::thread;
+use std::time::{ Duration, Instant };
+
+/// Longest time a single run of mazetool may take
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Run mazetool with the given arguments and wait for it to exit
+///
+/// # Parameters
+///
+/// * `args`            - Command line arguments
+///
+/// Returns the output of the process. Panics if it doesn't exit in time.
+///
+fn run_mazetool(args: &[&str]) -> Output
+{
+	let mut child = Command::new(env!("CARGO_BIN_EXE_mazetool"))
+	                        .args(args)
+	                        .stdout(Stdio::piped())
+	                        .stderr(Stdio::piped())
+	                        .spawn()
+	                        .unwrap();
+
+	let started = Instant::now();
+	while child.try_wait().unwrap().is_none()
+	{
+		if started.elapsed() > TIMEOUT
+		{
+			child.kill().ok();
+			panic!("mazetool {} didn't exit", args.join(" "));
+		}
+		thread::sleep(Duration::from_millis(10));
+	}
+	child.wait_with_output().unwrap()
+}
+
+#[test]
+fn generate_exits_with_success()
+{
+	let output = run_mazetool(&["-q", "generate", "21", "21"]);
+	assert_eq!(output.status.code(), Some(0));
+	assert!(!output.stdout.is_empty());
+}
+
+#[test]
+fn failed_load_exits_with_failure()
+{
+	let output = run_mazetool(&["-q", "load", "/nonexistent/mazetool.maze"]);
+	assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn invalid_arguments_exit_with_error()
+{
+	let output = run_mazetool(&["-q", "generate", "1", "21"]);
+	assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn printed_maze_with_stats_can_be_loaded()
+{
+	for mode in ["--headless", "--raw"]
+	{
+		let output = run_mazetool(&["-q", mode, "--stats", "generate", "21", "21"]);
+		assert_eq!(output.status.code(), Some(0), "{}", mode);
+		assert!(String::from_utf8_lossy(&output.stdout).starts_with("Maze 21 21\n"), "{}", mode);
+		assert!(String::from_utf8_lossy(&output.stderr).contains("Passages: "), "{}", mode);
+
+		let mut path = std::env::temp_dir();
+		path.push(format!("mazetool_{}_printed_stats.maze", std::process::id()));
+		std::fs::write(&path, &output.stdout).unwrap();
+		let output = run_mazetool(&["-q", "load", path.to_str().unwrap()]);
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(output.status.code(), Some(0), "{}", mode);
+	}
+}